@@ -23,11 +23,11 @@ pub fn spi_recv(buffer: &mut [u8]) {
 pub mod model {
     use super::*;
     use crate::common::init_mock;
-    use regmock_rs::utils::Regmock;
+    use regmock_rs::model::{ModelHandle, PeripheralModel};
+    use regmock_rs::utils::{AccessContext, Regmock};
     use std::{
-        cell::RefCell,
-        sync::{Arc, Mutex, OnceLock},
-        thread::LocalKey,
+        ops::Range,
+        sync::{Arc, Mutex},
     };
 
     /// A very simple model of an SPI module
@@ -70,40 +70,29 @@ fn default() -> Self {
         }
     }
 
-    // Since rust testcases run multi-threaded, our model must be a thread-local
+    // Wiring the model up with regmock: all accesses to the SPI registers
+    // are routed to the model once it is attached.
     //
-    // Note: if we start a thread in our testcase, we only need to initialize
-    // the regmock thread-local on that thread, not the model. This is the
-    // case because we never directly access the model thread-local, only through
-    // boxed callback functions that we register
-    thread_local! {
-        #[allow(clippy::missing_const_for_thread_local)]
-        pub static SPI_MODEL: OnceLock<RefCell<SpiModel>> = const { OnceLock::new() };
-    }
+    // Since regmock owns the model, there is no need to keep it in a
+    // thread-local ourselves. The model can still be accessed through
+    // the handle returned when attaching it.
+    impl PeripheralModel for SpiModel {
+        fn range(&self) -> Range<usize> {
+            SPI.status().addr()..SPI.rx().addr() + 4
+        }
 
-    impl SpiModel {
-        /// Register all the read callback functions with our regmock instance
-        pub fn register(
-            regmock: Arc<Mutex<Regmock>>,
-        ) -> &'static LocalKey<OnceLock<RefCell<SpiModel>>> {
-            let mut regmock = regmock.lock().unwrap();
-            SPI_MODEL.with(|sm| sm.set(RefCell::new(SpiModel::default())).unwrap());
-
-            regmock.read_fn.insert(
-                SPI.status().addr(),
-                Box::new(|_, _| {
-                    SPI_MODEL.with(|sm| sm.get().unwrap().borrow_mut().read_status()) as u64
-                }),
-            );
-            regmock.read_fn.insert(
-                SPI.rx().addr(),
-                Box::new(|_, _| {
-                    SPI_MODEL.with(|sm| sm.get().unwrap().borrow_mut().read_rx()) as u64
-                }),
-            );
-            &SPI_MODEL
+        fn on_read(&mut self, ctx: &mut AccessContext) -> u64 {
+            if ctx.register == SPI.status().addr() {
+                self.read_status() as u64
+            } else if ctx.register == SPI.rx().addr() {
+                self.read_rx() as u64
+            } else {
+                ctx.before
+            }
         }
+    }
 
+    impl SpiModel {
         // the mock function for the `status` register
         //
         // Note: we have a lock on regmock when executing this function, so we don't
@@ -153,36 +142,15 @@ pub fn read_rx(&mut self) -> u32 {
         }
     }
 
-    // This wrapper is just a simple thing to make accessing the model
-    // and setting data, etc. much simpler
-    pub struct ModelWrapper {
-        model: &'static LocalKey<OnceLock<RefCell<SpiModel>>>,
-    }
-
-    impl ModelWrapper {
-        // helper to get the thread-local model
-        pub fn with<F>(&self, f: F)
-        where
-            F: FnOnce(&mut SpiModel),
-        {
-            self.model.with(|m| f(&mut m.get().unwrap().borrow_mut()));
-        }
-
-        // prepare data to be "received"
-        pub fn set_receive(&self, b: Vec<u8>) {
-            self.with(|sm| sm.to_receive = b);
-        }
-    }
-
     // the init function that we'll call in case we want to use the model
     // in a test - also does the regmock init if we want it to for convenience
     pub fn init_radio_model(
         regmock: Option<Arc<Mutex<Regmock>>>,
-    ) -> (Arc<Mutex<Regmock>>, ModelWrapper) {
+    ) -> (Arc<Mutex<Regmock>>, ModelHandle<SpiModel>) {
         let regmock = regmock.unwrap_or(init_mock(None));
-        let spi_model = SpiModel::register(regmock.clone());
+        let spi_model = regmock.lock().unwrap().attach_model(SpiModel::default());
 
-        (regmock, ModelWrapper { model: spi_model })
+        (regmock, spi_model)
     }
 }
 
@@ -194,8 +162,13 @@ mod tests {
     #[test]
     fn read_data() {
         // initialize and prepare data
-        let (_regmock, model) = init_radio_model(None);
-        model.set_receive(vec![0x11, 0x22, 0x33]);
+        let (regmock, model) = init_radio_model(None);
+        regmock
+            .lock()
+            .unwrap()
+            .model_mut(&model)
+            .unwrap()
+            .to_receive = vec![0x11, 0x22, 0x33];
 
         // call the DUT
         let mut buffer = [0; 3];
@@ -203,5 +176,14 @@ fn read_data() {
 
         // check the DUT received correctly
         assert_eq!(buffer, [0x11, 0x22, 0x33]);
+
+        // and that it consumed all the data of the model
+        assert!(regmock
+            .lock()
+            .unwrap()
+            .model(&model)
+            .unwrap()
+            .to_receive
+            .is_empty());
     }
 }
@@ -0,0 +1,152 @@
+//! Procedural macros for `regmock-rs`.
+//!
+//! Use them through the re-exports in `regmock_rs` with the `macros` feature
+//! enabled, e.g. `#[regmock_rs::test]`.
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{meta::ParseNestedMeta, parse_macro_input, FnArg, ItemFn, LitStr, Path};
+
+/// Arguments of the `#[regmock_rs::test]` attribute.
+#[derive(Default)]
+struct TestArgs {
+    pac: Option<Path>,
+    resolver: Option<Path>,
+    svd: Option<LitStr>,
+}
+
+impl TestArgs {
+    fn parse(&mut self, meta: ParseNestedMeta) -> syn::Result<()> {
+        if meta.path.is_ident("pac") {
+            self.pac = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("resolver") {
+            self.resolver = Some(meta.value()?.parse()?);
+        } else if meta.path.is_ident("svd") {
+            self.svd = Some(meta.value()?.parse()?);
+        } else {
+            return Err(meta.error("expected `pac`, `resolver` or `svd`"));
+        }
+        if self.resolver.is_some() && self.svd.is_some() {
+            return Err(meta.error("`resolver` and `svd` can not be combined"));
+        }
+        Ok(())
+    }
+
+    /// Expression constructing the `Regmock` of the test.
+    fn regmock(&self) -> TokenStream2 {
+        match (&self.resolver, &self.svd) {
+            (Some(resolver), _) => {
+                quote!(::regmock_rs::utils::Regmock::with_resolver(&#resolver))
+            }
+            (_, Some(svd)) => quote! {
+                ::regmock_rs::utils::Regmock::from_svd(#svd)
+                    .unwrap_or_else(|e| panic!("Could not load SVD file {}: {:?}", #svd, e))
+            },
+            _ => quote!(::regmock_rs::utils::Regmock::default()),
+        }
+    }
+}
+
+/// Run a test against a fresh `Regmock`.
+///
+/// The attribute replaces `#[test]`. Before the test body runs, a new
+/// `Regmock` is installed on the test thread with
+/// `regmock_rs::harness::TestHarness`. After the test, the checks
+/// registered with `Regmock::at_end_of_test` are verified, and the access
+/// log is printed if the test failed.
+///
+/// The test function can take one argument, which is bound to the
+/// installed `Arc<Mutex<Regmock>>`.
+///
+/// # Arguments
+///
+/// - `pac = path::to::pac`: register `regmock_rs::read_fn` and
+///   `regmock_rs::write_fn` with the tracing hooks of the PAC.
+/// - `resolver = path::to::fn`: resolve register names with the given
+///   function, see `Regmock::with_resolver`.
+/// - `svd = "path/to/chip.svd"`: load register metadata and reset values
+///   from an SVD file relative to the package root, see `Regmock::from_svd`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// #[regmock_rs::test(pac = pac, resolver = pac::reg_name::reg_name_from_addr)]
+/// fn enables_spi(mock: Arc<Mutex<Regmock>>) {
+///     mock.lock().unwrap().at_end_of_test(|mock| { /* ... */ Ok(()) });
+///     unsafe { pac::SPI.ctrl().write(pac::spi::Ctrl::new(0x1)) };
+/// }
+/// ```
+#[proc_macro_attribute]
+pub fn test(args: TokenStream, item: TokenStream) -> TokenStream {
+    let mut test_args = TestArgs::default();
+    let parser = syn::meta::parser(|meta| test_args.parse(meta));
+    parse_macro_input!(args with parser);
+    let item = parse_macro_input!(item as ItemFn);
+    expand_test(test_args, item)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand_test(args: TestArgs, mut item: ItemFn) -> syn::Result<TokenStream2> {
+    let sig = &item.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "async tests are not supported",
+        ));
+    }
+    if !sig.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &sig.generics,
+            "test functions can not be generic",
+        ));
+    }
+    if sig.inputs.len() > 1 {
+        return Err(syn::Error::new_spanned(
+            &sig.inputs,
+            "test functions take at most one argument, the mock",
+        ));
+    }
+
+    let mock_arg = match item.sig.inputs.pop().map(|arg| arg.into_value()) {
+        Some(FnArg::Typed(arg)) => {
+            let (pat, ty) = (&arg.pat, &arg.ty);
+            quote!(let #pat: #ty = __regmock_harness.mock();)
+        }
+        Some(FnArg::Receiver(receiver)) => {
+            return Err(syn::Error::new_spanned(
+                receiver,
+                "test functions can not take `self`",
+            ));
+        }
+        None => TokenStream2::new(),
+    };
+    let hooks = args.pac.as_ref().map(|pac| {
+        // the hooks can only be set once per thread and always forward
+        // to the installed mock, so setting them again is not an error
+        quote! {
+            let _ = #pac::tracing::set_read_fn(::regmock_rs::read_fn);
+            let _ = #pac::tracing::set_write_fn(::regmock_rs::write_fn);
+        }
+    });
+    let regmock = args.regmock();
+
+    let ItemFn {
+        attrs,
+        vis,
+        sig,
+        block,
+    } = item;
+    Ok(quote! {
+        #[::core::prelude::v1::test]
+        #(#attrs)*
+        #vis #sig {
+            let __regmock_harness = ::regmock_rs::harness::TestHarness::new(#regmock);
+            #hooks
+            #mock_arg
+            // temporaries of the body must not outlive the harness
+            let __regmock_result = #block;
+            __regmock_harness.finish(__regmock_result)
+        }
+    })
+}
@@ -0,0 +1,407 @@
+//! Register metadata that gives the raw addresses and values recorded by
+//! [`Regmock`](crate::utils::Regmock) a human-readable meaning.
+//!
+//! A [`RegisterDatabase`] is usually loaded from the same SVD file that was
+//! used to generate the PAC, see [`RegisterDatabase::from_svd`].
+use std::collections::BTreeMap;
+use std::ops::Range;
+use std::path::Path;
+
+use crate::svd::{self, SvdError};
+
+/// Access rights of a register or bitfield.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Access {
+    /// Register can only be read.
+    ReadOnly,
+    /// Register can only be written.
+    WriteOnly,
+    /// Register can be read and written.
+    #[default]
+    ReadWrite,
+    /// Register can only be written once after reset, reads are undefined.
+    WriteOnce,
+    /// Register can be read, but only written once after reset.
+    ReadWriteOnce,
+}
+
+impl Access {
+    /// Parse the SVD representation of the access rights.
+    pub fn from_svd(access: &str) -> Option<Self> {
+        match access {
+            "read-only" => Some(Self::ReadOnly),
+            "write-only" => Some(Self::WriteOnly),
+            "read-write" => Some(Self::ReadWrite),
+            "writeOnce" => Some(Self::WriteOnce),
+            "read-writeOnce" => Some(Self::ReadWriteOnce),
+            _ => None,
+        }
+    }
+
+    /// Whether reading with these access rights is allowed.
+    pub fn is_readable(&self) -> bool {
+        matches!(self, Self::ReadOnly | Self::ReadWrite | Self::ReadWriteOnce)
+    }
+
+    /// Whether writing with these access rights is allowed.
+    pub fn is_writable(&self) -> bool {
+        !matches!(self, Self::ReadOnly)
+    }
+}
+
+/// Effect of a write on the bits of a bitfield, as given by the SVD
+/// `modifiedWriteValues` element.
+///
+/// [`WriteAction::Ignore`] is not part of the SVD format and is used for
+/// reserved and **read-only** bits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WriteAction {
+    /// Writing `1` clears the bit, writing `0` has no effect.
+    OneToClear,
+    /// Writing `1` sets the bit, writing `0` has no effect.
+    OneToSet,
+    /// Writing `1` toggles the bit, writing `0` has no effect.
+    OneToToggle,
+    /// Writing `0` clears the bit, writing `1` has no effect.
+    ZeroToClear,
+    /// Writing `0` sets the bit, writing `1` has no effect.
+    ZeroToSet,
+    /// Writing `0` toggles the bit, writing `1` has no effect.
+    ZeroToToggle,
+    /// Any write clears all bits.
+    Clear,
+    /// Any write sets all bits.
+    Set,
+    /// Writes modify the bits in an unspecified way, the written value is stored.
+    Modify,
+    /// Writes have no effect.
+    Ignore,
+}
+
+impl WriteAction {
+    /// Parse the SVD representation of the `modifiedWriteValues`.
+    pub fn from_svd(action: &str) -> Option<Self> {
+        match action {
+            "oneToClear" => Some(Self::OneToClear),
+            "oneToSet" => Some(Self::OneToSet),
+            "oneToToggle" => Some(Self::OneToToggle),
+            "zeroToClear" => Some(Self::ZeroToClear),
+            "zeroToSet" => Some(Self::ZeroToSet),
+            "zeroToToggle" => Some(Self::ZeroToToggle),
+            "clear" => Some(Self::Clear),
+            "set" => Some(Self::Set),
+            "modify" => Some(Self::Modify),
+            _ => None,
+        }
+    }
+
+    /// Value of the bits selected by `mask` after writing `value` to a register
+    /// that held `before`. Bits outside of `mask` are `0`.
+    pub fn apply(&self, mask: u64, before: u64, value: u64) -> u64 {
+        let updated = match self {
+            Self::OneToClear => before & !value,
+            Self::OneToSet => before | value,
+            Self::OneToToggle => before ^ value,
+            Self::ZeroToClear => before & value,
+            Self::ZeroToSet => before | !value,
+            Self::ZeroToToggle => before ^ !value,
+            Self::Clear => 0,
+            Self::Set => u64::MAX,
+            Self::Modify => value,
+            Self::Ignore => before,
+        };
+        updated & mask
+    }
+}
+
+/// Side effect of a read on the bits of a bitfield, as given by the SVD
+/// `readAction` element.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReadAction {
+    /// The bits are cleared after the read.
+    Clear,
+    /// The bits are set after the read.
+    Set,
+    /// The bits are modified in an unspecified way by the read.
+    Modify,
+    /// The read has a side effect outside of the register.
+    ModifyExternal,
+}
+
+impl ReadAction {
+    /// Parse the SVD representation of the `readAction`.
+    pub fn from_svd(action: &str) -> Option<Self> {
+        match action {
+            "clear" => Some(Self::Clear),
+            "set" => Some(Self::Set),
+            "modify" => Some(Self::Modify),
+            "modifyExternal" => Some(Self::ModifyExternal),
+            _ => None,
+        }
+    }
+
+    /// Value of the bits selected by `mask` after reading a register that
+    /// held `before`. Bits outside of `mask` are `0`.
+    pub fn apply(&self, mask: u64, before: u64) -> u64 {
+        let updated = match self {
+            Self::Clear => 0,
+            Self::Set => u64::MAX,
+            Self::Modify | Self::ModifyExternal => before,
+        };
+        updated & mask
+    }
+}
+
+/// Layout of a bitfield inside of a register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldInfo {
+    /// Name of the bitfield.
+    pub name: String,
+    /// Position of the least significant bit of the bitfield.
+    pub bit_offset: usize,
+    /// Number of bits of the bitfield.
+    pub bit_width: usize,
+    /// Access rights of the bitfield.
+    pub access: Access,
+    /// Effect of writes to the bitfield (SVD `modifiedWriteValues`).
+    pub write_action: Option<WriteAction>,
+    /// Side effect of reads from the bitfield (SVD `readAction`).
+    pub read_action: Option<ReadAction>,
+}
+
+impl FieldInfo {
+    /// Mask of the bits in the register that belong to the bitfield.
+    pub fn mask(&self) -> u64 {
+        svd::width_mask(self.bit_width) << self.bit_offset
+    }
+
+    /// Extract the value of the bitfield from a register value.
+    pub fn extract(&self, register_value: u64) -> u64 {
+        (register_value & self.mask()) >> self.bit_offset
+    }
+}
+
+/// Metadata of a single register.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RegisterInfo {
+    /// Name of the register as it is accessed through the PAC, e.g. `SPI.status()`.
+    pub name: String,
+    /// Name of the peripheral the register belongs to.
+    pub peripheral: String,
+    /// Absolute address of the register.
+    pub address: usize,
+    /// Size of the register in bits.
+    pub size: usize,
+    /// Access rights of the register.
+    pub access: Access,
+    /// Value of the register after reset. Bits outside of
+    /// [`reset_mask`](#structfield.reset_mask) are `0`.
+    pub reset_value: u64,
+    /// Bits of the register that have a defined reset value.
+    pub reset_mask: u64,
+    /// Bitfields of the register, ordered by their bit offset.
+    pub fields: Vec<FieldInfo>,
+}
+
+impl RegisterInfo {
+    /// Range of byte addresses covered by the register.
+    pub fn range(&self) -> Range<usize> {
+        self.address..self.address + self.size.div_ceil(8)
+    }
+
+    /// Render the values of all bitfields of the register, e.g. `en=0x1 cpha=0x0 cpol=0x0`.
+    pub fn describe_value(&self, value: u64) -> String {
+        self.fields
+            .iter()
+            .map(|f| format!("{}={:#x}", f.name, f.extract(value)))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// Collection of [`RegisterInfo`]s indexed by their address.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// let database = RegisterDatabase::from_svd("path/to/chip.svd").unwrap();
+/// assert_eq!(database.name(pac::SPI.status().addr()), Some("SPI.status()"));
+/// let mock = Regmock::with_database(database);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RegisterDatabase {
+    registers: BTreeMap<usize, RegisterInfo>,
+}
+
+impl RegisterDatabase {
+    /// Construct an empty [`RegisterDatabase`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Load the metadata of all registers described in the SVD file at `path`.
+    pub fn from_svd(path: impl AsRef<Path>) -> Result<Self, SvdError> {
+        Ok(svd::load(path)?.into_iter().collect())
+    }
+
+    /// Load the metadata of all registers described in the SVD passed as string.
+    pub fn from_svd_str(svd: &str) -> Result<Self, SvdError> {
+        Ok(svd::parse(svd)?.into_iter().collect())
+    }
+
+    /// Add a register to the database.
+    ///
+    /// If a register with the same address already exists, it is replaced and returned.
+    pub fn insert(&mut self, register: RegisterInfo) -> Option<RegisterInfo> {
+        self.registers.insert(register.address, register)
+    }
+
+    /// Get the metadata of the register at `addr`.
+    pub fn get(&self, addr: usize) -> Option<&RegisterInfo> {
+        self.registers.get(&addr)
+    }
+
+    /// Get the name of the register at `addr`.
+    pub fn name(&self, addr: usize) -> Option<&str> {
+        self.get(addr).map(|r| r.name.as_str())
+    }
+
+    /// Get the register whose address range contains `addr`.
+    pub fn containing(&self, addr: usize) -> Option<&RegisterInfo> {
+        self.registers
+            .range(..=addr)
+            .next_back()
+            .map(|(_, r)| r)
+            .filter(|r| r.range().contains(&addr))
+    }
+
+    /// Get the address range spanned by the registers of `peripheral`.
+    pub fn peripheral_range(&self, peripheral: &str) -> Option<Range<usize>> {
+        self.registers
+            .values()
+            .filter(|r| r.peripheral == peripheral)
+            .map(RegisterInfo::range)
+            .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+    }
+
+    /// Get the register whose address range is closest to `addr`.
+    pub fn nearest(&self, addr: usize) -> Option<&RegisterInfo> {
+        let below = self.registers.range(..=addr).next_back().map(|(_, r)| r);
+        let above = self.registers.range(addr..).next().map(|(_, r)| r);
+        [below, above]
+            .into_iter()
+            .flatten()
+            .min_by_key(|r| distance(&r.range(), addr))
+    }
+
+    /// Iterate over all registers ordered by address.
+    pub fn iter(&self) -> impl Iterator<Item = &RegisterInfo> {
+        self.registers.values()
+    }
+
+    /// Number of registers in the database.
+    pub fn len(&self) -> usize {
+        self.registers.len()
+    }
+
+    /// Whether the database contains no registers.
+    pub fn is_empty(&self) -> bool {
+        self.registers.is_empty()
+    }
+}
+
+impl FromIterator<RegisterInfo> for RegisterDatabase {
+    fn from_iter<T: IntoIterator<Item = RegisterInfo>>(iter: T) -> Self {
+        let mut database = Self::new();
+        for register in iter {
+            database.insert(register);
+        }
+        database
+    }
+}
+
+/// A named range of addresses in a [`MemoryMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoryRegion {
+    /// Name of the region, e.g. a register or peripheral name.
+    pub name: String,
+    /// Byte addresses covered by the region.
+    pub range: Range<usize>,
+}
+
+/// Set of address ranges that may be accessed through the PAC.
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::database::MemoryMap;
+///
+/// let mut map = MemoryMap::new();
+/// map.add("SPI", 0x8200..0x8210);
+/// assert!(map.contains(0x8204, 4));
+/// assert!(!map.contains(0x820E, 4));
+/// assert_eq!(map.nearest(0x8300).unwrap().name, "SPI");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MemoryMap {
+    regions: Vec<MemoryRegion>,
+}
+
+impl MemoryMap {
+    /// Construct an empty [`MemoryMap`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Construct a [`MemoryMap`] with one region per register in `database`.
+    pub fn from_database(database: &RegisterDatabase) -> Self {
+        Self {
+            regions: database
+                .iter()
+                .map(|r| MemoryRegion {
+                    name: r.name.clone(),
+                    range: r.range(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Add a named region of addresses to the map.
+    pub fn add(&mut self, name: impl Into<String>, range: Range<usize>) {
+        self.regions.push(MemoryRegion {
+            name: name.into(),
+            range,
+        });
+    }
+
+    /// Whether an access of `len` bytes at `addr` lies completely within one region.
+    pub fn contains(&self, addr: usize, len: usize) -> bool {
+        self.regions.iter().any(|r| {
+            r.range.start <= addr
+                && addr
+                    .checked_add(len.max(1))
+                    .is_some_and(|end| end <= r.range.end)
+        })
+    }
+
+    /// Get the region closest to `addr`.
+    pub fn nearest(&self, addr: usize) -> Option<&MemoryRegion> {
+        self.regions.iter().min_by_key(|r| distance(&r.range, addr))
+    }
+
+    /// Iterate over all regions of the map.
+    pub fn iter(&self) -> impl Iterator<Item = &MemoryRegion> {
+        self.regions.iter()
+    }
+}
+
+/// Distance of `addr` to the closest address in `range`.
+fn distance(range: &Range<usize>, addr: usize) -> usize {
+    if addr < range.start {
+        range.start - addr
+    } else if addr >= range.end {
+        addr - range.end + 1
+    } else {
+        0
+    }
+}
@@ -0,0 +1,394 @@
+//! Differences between the access logs of two test runs.
+//!
+//! [`RegmockLog::diff`] aligns the run-length-encoded entries of two logs by
+//! the type, address and length of their accesses and produces a full edit
+//! script: accesses that were removed, inserted, or that happened in both
+//! runs but with different values. The [`LogDiff`] can be rendered for test
+//! failure output, with register names and bitfield values by
+//! [`Regmock::format_diff`].
+use std::fmt::Display;
+
+use crate::database::RegisterInfo;
+use crate::utils::{RegisterAccess, Regmock, RegmockLog};
+
+/// Number of unchanged entries shown around a change when rendering a [`LogDiff`].
+const CONTEXT: usize = 2;
+
+/// Entry of a [`LogDiff`]. Accesses are paired with their run-length-encoding
+/// count, like in [`RegmockLog::log`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffEntry {
+    /// Access that happened in both runs with the same values.
+    Unchanged(RegisterAccess, usize),
+    /// Access that only happened in the old run.
+    Removed(RegisterAccess, usize),
+    /// Access that only happened in the new run.
+    Inserted(RegisterAccess, usize),
+    /// Access that happened in both runs, but with different values or a
+    /// different number of consecutive **`READ`**s.
+    Changed {
+        /// Access of the old run.
+        old: (RegisterAccess, usize),
+        /// Access of the new run.
+        new: (RegisterAccess, usize),
+    },
+}
+
+impl DiffEntry {
+    /// Check if the entry is a difference between the runs.
+    pub fn is_change(&self) -> bool {
+        !matches!(self, DiffEntry::Unchanged(..))
+    }
+}
+
+/// Edit script that turns one [`RegmockLog`] into another, see [`RegmockLog::diff`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogDiff {
+    /// Entries of both logs in order, the old entry before the new one.
+    pub entries: Vec<DiffEntry>,
+}
+
+impl LogDiff {
+    /// Check if the logs have no differences.
+    pub fn is_empty(&self) -> bool {
+        self.changes().next().is_none()
+    }
+
+    /// Iterate over the differences between the logs.
+    pub fn changes(&self) -> impl Iterator<Item = &DiffEntry> {
+        self.entries.iter().filter(|entry| entry.is_change())
+    }
+}
+
+impl Display for LogDiff {
+    /// Render the diff with register addresses, see [`Regmock::format_diff`].
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&render(self, |_| None, |_| None))
+    }
+}
+
+impl RegmockLog {
+    /// Compute the differences from `self` (the old run) to `other` (the new run).
+    ///
+    /// Entries are aligned by a longest common subsequence of the type,
+    /// address and length of their accesses. Aligned entries with different
+    /// values before or after the access, masks or **`READ`** counts are
+    /// reported as [`DiffEntry::Changed`]. Timing, sequence numbers, threads
+    /// and locations are ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::diff::DiffEntry;
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut old = Regmock::default();
+    /// old.write_volatile(0x100, 4, 0x1);
+    /// old.write_volatile(0x104, 4, 0x1);
+    ///
+    /// let mut new = Regmock::default();
+    /// new.write_volatile(0x100, 4, 0x3);
+    /// new.write_volatile(0x104, 4, 0x1);
+    /// new.write_volatile(0x108, 4, 0x1);
+    ///
+    /// let diff = old.log.diff(&new.log);
+    /// assert_eq!(diff.changes().count(), 2);
+    /// assert!(matches!(diff.entries[0], DiffEntry::Changed { .. }));
+    /// assert!(matches!(diff.entries[2], DiffEntry::Inserted(..)));
+    /// ```
+    pub fn diff(&self, other: &RegmockLog) -> LogDiff {
+        let (old, new) = (&self.log, &other.log);
+        let prefix = old
+            .iter()
+            .zip(new)
+            .take_while(|(o, n)| same_values(o, n))
+            .count();
+        let suffix = old[prefix..]
+            .iter()
+            .rev()
+            .zip(new[prefix..].iter().rev())
+            .take_while(|(o, n)| same_values(o, n))
+            .count();
+        let (old_mid, new_mid) = (
+            &old[prefix..old.len() - suffix],
+            &new[prefix..new.len() - suffix],
+        );
+
+        let mut entries: Vec<_> = old[..prefix]
+            .iter()
+            .map(|(access, count)| DiffEntry::Unchanged(access.clone(), *count))
+            .collect();
+        entries.extend(align(old_mid, new_mid));
+        entries.extend(
+            old[old.len() - suffix..]
+                .iter()
+                .map(|(access, count)| DiffEntry::Unchanged(access.clone(), *count)),
+        );
+        LogDiff { entries }
+    }
+}
+
+/// Check if two entries are the same access, regardless of their values.
+fn same_access(old: &(RegisterAccess, usize), new: &(RegisterAccess, usize)) -> bool {
+    old.0.ty == new.0.ty && old.0.addr == new.0.addr && old.0.len == new.0.len
+}
+
+/// Check if two entries are the same access with the same values.
+fn same_values(old: &(RegisterAccess, usize), new: &(RegisterAccess, usize)) -> bool {
+    same_access(old, new)
+        && old.0.before == new.0.before
+        && old.0.after == new.0.after
+        && old.0.mask == new.0.mask
+        && old.1 == new.1
+}
+
+/// Align the entries of two logs with a longest common subsequence of their accesses.
+fn align(old: &[(RegisterAccess, usize)], new: &[(RegisterAccess, usize)]) -> Vec<DiffEntry> {
+    edit_script(old, new, same_access)
+        .into_iter()
+        .map(|edit| match edit {
+            Edit::Both(i, j) if same_values(&old[i], &new[j]) => {
+                DiffEntry::Unchanged(old[i].0.clone(), old[i].1)
+            }
+            Edit::Both(i, j) => DiffEntry::Changed {
+                old: old[i].clone(),
+                new: new[j].clone(),
+            },
+            Edit::Old(i) => DiffEntry::Removed(old[i].0.clone(), old[i].1),
+            Edit::New(j) => DiffEntry::Inserted(new[j].0.clone(), new[j].1),
+        })
+        .collect()
+}
+
+/// Step of an edit script between two sequences, holding the indices of the items.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Edit {
+    /// Item of both sequences.
+    Both(usize, usize),
+    /// Item only in the old sequence.
+    Old(usize),
+    /// Item only in the new sequence.
+    New(usize),
+}
+
+/// Compute the edit script from `old` to `new` along a longest common
+/// subsequence of the items that `same` considers the same.
+///
+/// Uses Hirschberg's algorithm, which needs memory linear in the length of
+/// the sequences, so long logs of polling loops can be compared.
+pub(crate) fn edit_script<T>(old: &[T], new: &[T], same: impl Fn(&T, &T) -> bool) -> Vec<Edit> {
+    let mut edits = Vec::with_capacity(old.len().max(new.len()));
+    hirschberg(old, new, (0, 0), &same, &mut edits);
+    edits
+}
+
+/// Append the edit script from `old` to `new`, which start at the indices
+/// `offset` of the whole sequences, to `edits`.
+fn hirschberg<T>(
+    old: &[T],
+    new: &[T],
+    offset: (usize, usize),
+    same: &impl Fn(&T, &T) -> bool,
+    edits: &mut Vec<Edit>,
+) {
+    let (i0, j0) = offset;
+    let inserted = |range: std::ops::Range<usize>| range.map(move |j| Edit::New(j0 + j));
+    match old {
+        [] => edits.extend(inserted(0..new.len())),
+        [item] => match new.iter().position(|n| same(item, n)) {
+            Some(j) => {
+                edits.extend(inserted(0..j));
+                edits.push(Edit::Both(i0, j0 + j));
+                edits.extend(inserted(j + 1..new.len()));
+            }
+            None => {
+                edits.push(Edit::Old(i0));
+                edits.extend(inserted(0..new.len()));
+            }
+        },
+        _ if new.is_empty() => edits.extend((0..old.len()).map(|i| Edit::Old(i0 + i))),
+        _ => {
+            // split `new` where the LCS of the first half of `old` ends, the
+            // first split of the longest LCS keeps removed items first
+            let mid = old.len() / 2;
+            let split = {
+                let forward = lcs_lengths(old[..mid].iter(), new.iter(), same);
+                let backward = lcs_lengths(old[mid..].iter().rev(), new.iter().rev(), same);
+                (0..=new.len())
+                    .rev()
+                    .max_by_key(|&k| forward[k] + backward[new.len() - k])
+                    .unwrap_or(0)
+            };
+            hirschberg(&old[..mid], &new[..split], (i0, j0), same, edits);
+            hirschberg(
+                &old[mid..],
+                &new[split..],
+                (i0 + mid, j0 + split),
+                same,
+                edits,
+            );
+        }
+    }
+}
+
+/// Compute the lengths of the LCS of all of `old` and each prefix of `new`,
+/// keeping a single row of the LCS table.
+fn lcs_lengths<'a, T: 'a>(
+    old: impl Iterator<Item = &'a T>,
+    new: impl Iterator<Item = &'a T> + Clone,
+    same: &impl Fn(&T, &T) -> bool,
+) -> Vec<usize> {
+    let mut row = vec![0; new.clone().count() + 1];
+    for o in old {
+        // value of the previous row at `j`
+        let mut diagonal = 0;
+        for (j, n) in new.clone().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if same(o, n) {
+                diagonal + 1
+            } else {
+                above.max(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row
+}
+
+impl Regmock {
+    /// Render a [`LogDiff`] in a human-readable way, using the register names
+    /// and bitfields if they are known.
+    ///
+    /// Removed entries start with `-`, inserted entries with `+` and changed
+    /// entries with `~`, followed by the changed values and bitfields.
+    /// Long runs of unchanged entries are collapsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut old = Regmock::default();
+    /// old.write_volatile(0x100, 4, 0x1);
+    /// let mut new = Regmock::default();
+    /// new.write_volatile(0x100, 4, 0x3);
+    /// new.write_volatile(0x104, 4, 0x1);
+    ///
+    /// assert_eq!(
+    ///     new.format_diff(&old.log.diff(&new.log)),
+    ///     "~ WRITE 0x00000100 len:4: after 0x00000001 -> 0x00000003\n\
+    ///      + WRITE 0x00000104 len:4 0x00000000 -> 0x00000001"
+    /// );
+    /// ```
+    pub fn format_diff(&self, diff: &LogDiff) -> String {
+        render(
+            diff,
+            |addr| self.get_reg_name(addr).map(str::to_owned),
+            |addr| {
+                self.database
+                    .as_ref()?
+                    .get(addr)
+                    .filter(|r| r.address == addr)
+            },
+        )
+    }
+}
+
+fn render<'a>(
+    diff: &LogDiff,
+    name: impl Fn(usize) -> Option<String>,
+    register: impl Fn(usize) -> Option<&'a RegisterInfo>,
+) -> String {
+    let describe = |access: &RegisterAccess| {
+        let mut text = match &access.ty {
+            Some(ty) => format!("{ty:?}"),
+            None => "ACCESS".to_owned(),
+        };
+        if let Some(addr) = access.addr {
+            match name(addr) {
+                Some(name) => text += &format!(" {name} (0x{addr:08X})"),
+                None => text += &format!(" 0x{addr:08X}"),
+            }
+        }
+        if let Some(len) = access.len {
+            text += &format!(" len:{len}");
+        }
+        text
+    };
+    let line = |prefix: char, access: &RegisterAccess, count: usize| {
+        let mut text = format!("{prefix} {}", describe(access));
+        match (access.before, access.after) {
+            (Some(before), Some(after)) => text += &format!(" 0x{before:08X} -> 0x{after:08X}"),
+            (Some(before), None) => text += &format!(" 0x{before:08X} -> ?"),
+            (None, Some(after)) => text += &format!(" -> 0x{after:08X}"),
+            (None, None) => {}
+        }
+        if count > 1 {
+            text += &format!(" (x{count})");
+        }
+        text
+    };
+
+    let near_change = |index: usize| {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(diff.entries.len());
+        diff.entries[start..end].iter().any(DiffEntry::is_change)
+    };
+    let mut lines = Vec::new();
+    let mut skipped = 0;
+    for (index, entry) in diff.entries.iter().enumerate() {
+        if !entry.is_change() && !near_change(index) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            lines.push(format!("  ... {skipped} unchanged"));
+            skipped = 0;
+        }
+        lines.push(match entry {
+            DiffEntry::Unchanged(access, count) => line(' ', access, *count),
+            DiffEntry::Removed(access, count) => line('-', access, *count),
+            DiffEntry::Inserted(access, count) => line('+', access, *count),
+            DiffEntry::Changed { old, new } => {
+                let mut changes = Vec::new();
+                let mut value = |label: &str, old: Option<u64>, new: Option<u64>| {
+                    if old != new {
+                        let hex =
+                            |v: Option<u64>| v.map_or("?".to_owned(), |v| format!("0x{v:08X}"));
+                        changes.push(format!("{label} {} -> {}", hex(old), hex(new)));
+                    }
+                };
+                value("before", old.0.before, new.0.before);
+                value("after", old.0.after, new.0.after);
+                value("mask", old.0.mask, new.0.mask);
+                if old.1 != new.1 {
+                    changes.push(format!("x{} -> x{}", old.1, new.1));
+                }
+                let mut text = format!("~ {}: {}", describe(&new.0), changes.join(", "));
+                let fields = new
+                    .0
+                    .addr
+                    .and_then(&register)
+                    .zip(old.0.after.zip(new.0.after));
+                if let Some((register, (old, new))) = fields {
+                    let fields: Vec<_> = register
+                        .fields
+                        .iter()
+                        .filter(|f| f.extract(old) != f.extract(new))
+                        .map(|f| {
+                            format!("{}: {:#x} -> {:#x}", f.name, f.extract(old), f.extract(new))
+                        })
+                        .collect();
+                    if !fields.is_empty() {
+                        text += &format!(" [{}]", fields.join(", "));
+                    }
+                }
+                text
+            }
+        });
+    }
+    if skipped > 0 {
+        lines.push(format!("  ... {skipped} unchanged"));
+    }
+    lines.join("\n")
+}
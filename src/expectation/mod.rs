@@ -0,0 +1,229 @@
+//! Declarative expectations on the register accesses of a test.
+//!
+//! Expectations are registered on a [`Regmock`](crate::utils::Regmock) before
+//! the code under test runs, see [`Regmock::expect_write`](crate::utils::Regmock::expect_write).
+//! Every access is checked against them as it happens: an access that
+//! contradicts an expectation `panic!()`s right away, so the backtrace
+//! points at the offending driver code. Expectations that were not met are
+//! reported by [`Regmock::verify`](crate::utils::Regmock::verify), which is
+//! called when the mock or the guard it was installed with is dropped.
+use std::fmt::Display;
+use std::ops::Range;
+use std::panic::Location;
+use std::sync::{Arc, Mutex};
+
+use crate::utils::RegisterAccess;
+
+/// Type of the accesses an [`Expectation`] applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExpectedAccess {
+    /// Reads of the register.
+    Read,
+    /// Writes and other accesses that write the register, see
+    /// [`RegisterAccess::is_write`].
+    Write,
+    /// Any access.
+    Any,
+}
+
+/// Expected accesses to a register or address range.
+///
+/// Constructed by [`Regmock::expect_read`](crate::utils::Regmock::expect_read),
+/// [`Regmock::expect_write`](crate::utils::Regmock::expect_write) and
+/// [`Regmock::expect_no_access`](crate::utils::Regmock::expect_no_access)
+/// and refined with the builder methods. By default, an expectation has to
+/// be met at least once.
+#[derive(Debug)]
+pub struct Expectation {
+    access: ExpectedAccess,
+    range: Range<usize>,
+    value: Option<u64>,
+    min: usize,
+    max: Option<usize>,
+    sequence: Option<(Sequence, usize)>,
+    calls: usize,
+    location: &'static Location<'static>,
+}
+
+impl Expectation {
+    pub(crate) fn new(
+        access: ExpectedAccess,
+        range: Range<usize>,
+        location: &'static Location<'static>,
+    ) -> Self {
+        Self {
+            access,
+            range,
+            value: None,
+            min: 1,
+            max: None,
+            sequence: None,
+            calls: 0,
+            location,
+        }
+    }
+
+    /// Only match accesses after which the register holds `value`.
+    ///
+    /// For writes this is the written value after the bitfield semantics
+    /// and callbacks were applied, for reads the value that was read.
+    pub fn with_value(&mut self, value: u64) -> &mut Self {
+        self.value = Some(value);
+        self
+    }
+
+    /// Expect exactly `n` matching accesses.
+    pub fn times(&mut self, n: usize) -> &mut Self {
+        self.min = n;
+        self.max = Some(n);
+        self
+    }
+
+    /// Expect at least `n` matching accesses.
+    pub fn at_least(&mut self, n: usize) -> &mut Self {
+        self.min = n;
+        self.max = None;
+        self
+    }
+
+    /// Expect at most `n` matching accesses.
+    pub fn at_most(&mut self, n: usize) -> &mut Self {
+        self.min = 0;
+        self.max = Some(n);
+        self
+    }
+
+    /// Expect no matching access.
+    pub fn never(&mut self) -> &mut Self {
+        self.times(0)
+    }
+
+    /// Expect the accesses to happen after those of the expectations added
+    /// to `sequence` before, and before those of the expectations added later.
+    pub fn in_sequence(&mut self, sequence: &Sequence) -> &mut Self {
+        self.sequence = Some((sequence.clone(), sequence.push()));
+        self
+    }
+
+    /// Get the number of accesses that matched the expectation so far.
+    pub fn calls(&self) -> usize {
+        self.calls
+    }
+
+    /// Check if the expectation is met by the accesses so far.
+    pub fn is_satisfied(&self) -> bool {
+        self.calls >= self.min
+    }
+
+    /// Get the location in the source code where the expectation was added.
+    pub fn location(&self) -> &'static Location<'static> {
+        self.location
+    }
+
+    /// Check if the expectation is about `access`, regardless of its value.
+    pub(crate) fn applies_to(&self, access: &RegisterAccess) -> bool {
+        let ty = match self.access {
+            ExpectedAccess::Read => !access.is_write(),
+            ExpectedAccess::Write => access.is_write(),
+            ExpectedAccess::Any => true,
+        };
+        ty && access.addr.is_some_and(|addr| self.range.contains(&addr))
+    }
+
+    /// Check if `access` can be counted towards the expectation.
+    pub(crate) fn accepts(&self, access: &RegisterAccess) -> bool {
+        self.value.is_none_or(|value| access.after == Some(value))
+            && self.max.is_none_or(|max| self.calls < max)
+    }
+
+    pub(crate) fn call(&mut self) {
+        self.calls += 1;
+    }
+
+    pub(crate) fn sequence(&self) -> Option<&(Sequence, usize)> {
+        self.sequence.as_ref()
+    }
+}
+
+impl Display for Expectation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.access {
+            ExpectedAccess::Read => write!(f, "read of 0x{:08X}", self.range.start)?,
+            ExpectedAccess::Write => write!(f, "write to 0x{:08X}", self.range.start)?,
+            ExpectedAccess::Any => write!(
+                f,
+                "access to 0x{:08X}..0x{:08X}",
+                self.range.start, self.range.end
+            )?,
+        }
+        if let Some(value) = self.value {
+            write!(f, " with value 0x{value:X}")?;
+        }
+        match (self.min, self.max) {
+            (0, Some(0)) => write!(f, " never")?,
+            (min, Some(max)) if min == max => write!(f, " {min} time(s)")?,
+            (min, Some(max)) => write!(f, " {min} to {max} time(s)")?,
+            (min, None) => write!(f, " at least {min} time(s)")?,
+        }
+        write!(f, " (expected at {})", self.location)
+    }
+}
+
+/// Order in which the accesses of multiple [`Expectation`]s have to happen.
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::expectation::Sequence;
+/// use regmock_rs::utils::Regmock;
+///
+/// let mut mock = Regmock::default();
+/// let seq = Sequence::new();
+/// mock.expect_write(0x100).with_value(0x1).in_sequence(&seq);
+/// mock.expect_write(0x104).times(2).in_sequence(&seq);
+///
+/// mock.write_volatile(0x100, 4, 0x1);
+/// mock.write_volatile(0x104, 4, 0x2);
+/// mock.write_volatile(0x104, 4, 0x3);
+/// assert_eq!(mock.verify(), Ok(()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Sequence {
+    state: Arc<Mutex<SequenceState>>,
+}
+
+#[derive(Debug, Default)]
+struct SequenceState {
+    /// Number of expectations in the sequence.
+    len: usize,
+    /// Position of the expectation that matched last.
+    current: usize,
+}
+
+impl Sequence {
+    /// Construct an empty [`Sequence`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add an expectation to the sequence and return its position.
+    fn push(&self) -> usize {
+        let mut state = self.state.lock().unwrap();
+        state.len += 1;
+        state.len - 1
+    }
+
+    /// Get the position of the expectation that matched last.
+    pub(crate) fn current(&self) -> usize {
+        self.state.lock().unwrap().current
+    }
+
+    pub(crate) fn advance(&self, position: usize) {
+        self.state.lock().unwrap().current = position;
+    }
+
+    /// Check if `other` is the same sequence.
+    pub(crate) fn is(&self, other: &Sequence) -> bool {
+        Arc::ptr_eq(&self.state, &other.state)
+    }
+}
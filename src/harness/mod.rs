@@ -0,0 +1,125 @@
+//! Setup and teardown of tests that run against a [`Regmock`].
+//!
+//! A [`TestHarness`] installs a fresh mock on the test thread and verifies
+//! it when the test ends. It is what the `#[regmock_rs::test]` attribute
+//! (feature `macros`) expands to, but can also be used directly.
+use std::sync::{Arc, Mutex};
+
+use crate::utils::Regmock;
+use crate::{install, MockGuard};
+
+/// Installs a [`Regmock`] on the test thread for the lifetime of a test.
+///
+/// When the harness is dropped at the end of a passing test, the mock is
+/// verified with [`Regmock::verify`] and the test fails if an expectation or
+/// a check registered with [`Regmock::at_end_of_test`] is not met. If the test failed, the access log is
+/// printed instead to help with finding the cause. Tests that return a
+/// `Result` pass it through [`TestHarness::finish`] for an `Err` to count as
+/// failure.
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::harness::TestHarness;
+/// use regmock_rs::utils::Regmock;
+///
+/// let harness = TestHarness::new(Regmock::default());
+/// harness.mock().lock().unwrap().at_end_of_test(|mock| {
+///     match mock.log.len_full() {
+///         1 => Ok(()),
+///         n => Err(format!("expected one access, got {n}")),
+///     }
+/// });
+/// regmock_rs::write_fn(0x100, 4, 0x1);
+/// ```
+#[must_use = "the mock is uninstalled when the harness is dropped"]
+pub struct TestHarness {
+    guard: MockGuard,
+    /// The test returned an error, see [`TestHarness::finish`].
+    failed: bool,
+}
+
+/// Return value of a test function, see [`TestHarness::finish`].
+pub trait TestOutcome {
+    /// Whether the test failed by returning this value.
+    fn is_failure(&self) -> bool;
+}
+
+impl TestOutcome for () {
+    fn is_failure(&self) -> bool {
+        false
+    }
+}
+
+impl<T, E> TestOutcome for Result<T, E> {
+    fn is_failure(&self) -> bool {
+        self.is_err()
+    }
+}
+
+impl TestHarness {
+    /// Install `mock` as the `thread_local` [`Regmock`] object, see [`install`].
+    pub fn new(mock: Regmock) -> Self {
+        Self {
+            guard: install(Arc::new(Mutex::new(mock))),
+            failed: false,
+        }
+    }
+
+    /// End the test with the value returned by the test function.
+    ///
+    /// A test that returns an `Err` fails without panicking, so the access log
+    /// is printed like for a panic instead of verifying the mock.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::harness::TestHarness;
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// fn test() -> Result<(), String> {
+    ///     let harness = TestHarness::new(Regmock::default());
+    ///     let result = (|| {
+    ///         regmock_rs::write_fn(0x100, 4, 0x1);
+    ///         Ok(())
+    ///     })();
+    ///     harness.finish(result)
+    /// }
+    /// # test().unwrap();
+    /// ```
+    pub fn finish<R: TestOutcome>(mut self, result: R) -> R {
+        self.failed = result.is_failure();
+        result
+    }
+
+    /// Get the installed mock.
+    pub fn mock(&self) -> Arc<Mutex<Regmock>> {
+        self.guard.mock().clone()
+    }
+}
+
+impl Drop for TestHarness {
+    fn drop(&mut self) {
+        // a failed test may have poisoned the lock, its log is needed anyway
+        let mut mock = self
+            .guard
+            .mock()
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if std::thread::panicking() || self.failed {
+            eprintln!(
+                "register accesses of the failed test:\n{}",
+                mock.format_log()
+            );
+            // the test returned an error, the guard must not fail it again
+            if self.failed {
+                if let Err(failures) = mock.verify() {
+                    eprintln!("Regmock verification failed:\n{}", failures.join("\n"));
+                }
+            }
+        } else if let Err(failures) = mock.verify() {
+            drop(mock);
+            panic!("Regmock verification failed:\n{}", failures.join("\n"));
+        }
+    }
+}
@@ -0,0 +1,158 @@
+//! Simulated interrupt controller that lets interrupt-driven code run against
+//! a [`Regmock`](crate::utils::Regmock).
+//!
+//! Interrupt lines are connected to an [`Isr`] and optionally to an enable bit
+//! in a mocked register. Models and callbacks raise interrupts, the pending
+//! and enabled interrupts are then dispatched on the thread of the DUT between
+//! two register accesses, see [`crate::dispatch_interrupts`].
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Interrupt service routine registered for an interrupt line.
+pub type Isr = fn();
+
+/// Bits of a mocked register that enable an interrupt line.
+///
+/// The interrupt is enabled if any of the bits in `mask` is set in the
+/// register at `addr`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnableBit {
+    /// Address of the enable register.
+    pub addr: usize,
+    /// Enable bits in the register.
+    pub mask: u64,
+}
+
+impl EnableBit {
+    /// Construct an [`EnableBit`] for the bits in `mask` of the register at `addr`.
+    pub fn new(addr: usize, mask: u64) -> Self {
+        Self { addr, mask }
+    }
+}
+
+/// State of a single interrupt line.
+#[derive(Debug, Clone)]
+struct Line {
+    isr: Isr,
+    enable: Option<EnableBit>,
+    pending: bool,
+}
+
+/// Simulated interrupt controller of a [`Regmock`](crate::utils::Regmock).
+///
+/// Interrupts are identified by their number. If multiple interrupts are
+/// pending, the one with the lowest number is dispatched first. ISRs are not
+/// nested: interrupts raised while an ISR runs are dispatched after it returned.
+#[derive(Debug, Default)]
+pub struct InterruptController {
+    lines: BTreeMap<usize, Line>,
+    active: Option<usize>,
+}
+
+impl InterruptController {
+    /// Connect interrupt `irq` to `isr`.
+    ///
+    /// Without an `enable` bit, the interrupt is always enabled. Replaces
+    /// an existing connection of `irq`.
+    pub fn connect(&mut self, irq: usize, isr: Isr, enable: Option<EnableBit>) {
+        self.lines.insert(
+            irq,
+            Line {
+                isr,
+                enable,
+                pending: false,
+            },
+        );
+    }
+
+    /// Disconnect the ISR from interrupt `irq`.
+    pub fn disconnect(&mut self, irq: usize) {
+        self.lines.remove(&irq);
+    }
+
+    /// Mark interrupt `irq` as pending. Interrupts without a connected ISR
+    /// are ignored.
+    pub fn raise(&mut self, irq: usize) {
+        if let Some(line) = self.lines.get_mut(&irq) {
+            line.pending = true;
+        }
+    }
+
+    /// Clear the pending state of interrupt `irq`.
+    pub fn clear(&mut self, irq: usize) {
+        if let Some(line) = self.lines.get_mut(&irq) {
+            line.pending = false;
+        }
+    }
+
+    /// Clear the pending state of all interrupts.
+    pub fn clear_all(&mut self) {
+        for line in self.lines.values_mut() {
+            line.pending = false;
+        }
+    }
+
+    /// Check if interrupt `irq` is pending.
+    pub fn is_pending(&self, irq: usize) -> bool {
+        self.lines.get(&irq).is_some_and(|line| line.pending)
+    }
+
+    /// Get the number of the interrupt whose ISR is currently running.
+    pub fn active(&self) -> Option<usize> {
+        self.active
+    }
+
+    /// Get the pending interrupt that should be dispatched next.
+    ///
+    /// `register` gets the current value of an enable register.
+    pub(crate) fn next(&self, register: impl Fn(usize) -> u64) -> Option<(usize, Isr)> {
+        if self.active.is_some() {
+            return None;
+        }
+        self.lines
+            .iter()
+            .find(|(_, line)| {
+                line.pending
+                    && line
+                        .enable
+                        .is_none_or(|enable| register(enable.addr) & enable.mask != 0)
+            })
+            .map(|(irq, line)| (*irq, line.isr))
+    }
+
+    /// Mark interrupt `irq` as active and no longer pending.
+    pub(crate) fn enter(&mut self, irq: usize) {
+        self.clear(irq);
+        self.active = Some(irq);
+    }
+
+    /// Mark the active interrupt as finished.
+    pub(crate) fn exit(&mut self) {
+        self.active = None;
+    }
+}
+
+/// Kind of an [`InterruptEvent`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InterruptEventKind {
+    /// The ISR of the interrupt was called.
+    Enter,
+    /// The ISR of the interrupt returned.
+    Exit,
+}
+
+/// Entry or exit of an ISR recorded in [`RegmockLog::interrupts`](crate::utils::RegmockLog::interrupts).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InterruptEvent {
+    /// Number of the interrupt.
+    pub irq: usize,
+    /// Whether the ISR was entered or exited.
+    pub kind: InterruptEventKind,
+    /// Number of entries in [`RegmockLog::log`](crate::utils::RegmockLog::log)
+    /// before the event, i.e. where in the log the event happened.
+    pub log_index: usize,
+    /// Virtual time of the event.
+    pub time: Duration,
+}
@@ -1,27 +1,66 @@
 #![doc = include_str!("../README.md")]
 
 use std::{
-    sync::{Arc, Mutex, OnceLock},
+    cell::RefCell,
+    marker::PhantomData,
+    panic::Location,
+    sync::{Arc, Mutex},
     time::Duration,
 };
 
+pub mod database;
+pub mod diff;
+pub mod expectation;
+pub mod harness;
+pub mod interrupt;
+pub mod logfile;
 pub mod matchers;
+pub mod model;
+pub mod replay;
+pub mod snapshot;
+pub mod svd;
 pub mod utils;
+pub mod vcd;
 use crate::utils::Regmock;
 
+#[cfg(feature = "macros")]
+pub use regmock_macros::test;
+
 thread_local! {
-    #[allow(clippy::missing_const_for_thread_local)]
     /// Global Regmock object used by `read_fn`,`write_fn` and `ldmst_fn`
     /// to mock registers and chip behavior.
-    pub(crate) static  MOCK: ThreadLocalRegmock = const {OnceLock::new()};
+    pub(crate) static MOCK: RefCell<ThreadLocalRegmock> = const {
+        RefCell::new(ThreadLocalRegmock {
+            base: None,
+            scoped: Vec::new(),
+            installations: 0,
+        })
+    };
+}
+
+/// Mocks set on a thread: the mock set with [`init_regmock`] and the stack
+/// of mocks installed with [`install`], the last one is the active mock.
+pub(crate) struct ThreadLocalRegmock {
+    base: Option<Arc<Mutex<Regmock>>>,
+    /// Installed mocks with the number of their installation.
+    scoped: Vec<(u64, Arc<Mutex<Regmock>>)>,
+    /// Number of installations on the thread, identifies the [`MockGuard`]s.
+    installations: u64,
 }
 
-type ThreadLocalRegmock = OnceLock<Arc<Mutex<Regmock>>>;
+impl ThreadLocalRegmock {
+    fn active(&self) -> Option<&Arc<Mutex<Regmock>>> {
+        self.scoped
+            .last()
+            .map(|(_, mock)| mock)
+            .or(self.base.as_ref())
+    }
+}
 
 /// Errors generated when handling the `thread_local` locked [`Regmock`] object.
 #[derive(Debug, Clone)]
 pub enum MockError {
-    /// [`init_regmock`] was not called
+    /// [`init_regmock`] was not called and no mock is [`install`]ed
     MockNotInitialized,
     /// could not acquire lock to [`Regmock`] object.
     LockError,
@@ -34,31 +73,146 @@ fn from(value: MockError) -> Self {
 }
 
 /// Execute function against `thread_local` [`Regmock`] object.
+///
+/// Uses the mock installed last with [`install`], or the one set with
+/// [`init_regmock`] if no installed mock is in scope.
 pub fn with_mock<F, R>(f: F) -> Result<R, MockError>
 where
     F: FnOnce(&mut Regmock) -> R,
 {
-    MOCK.with(|mock| -> Result<R, MockError> {
-        let mut mock = mock
-            .get()
-            .ok_or(MockError::MockNotInitialized)?
-            .lock()
-            .map_err(|_| MockError::LockError)?;
-        Ok((f)(&mut mock))
+    let mock = MOCK
+        .with(|mock| mock.borrow().active().cloned())
+        .ok_or(MockError::MockNotInitialized)?;
+    let mut mock = mock.lock().map_err(|_| MockError::LockError)?;
+    Ok((f)(&mut mock))
+}
+
+/// Source file of the access functions, which `#[track_caller]` reports as
+/// the caller of accesses through a function pointer, see [`utils::AccessLocation`].
+pub(crate) const ACCESS_FN_FILE: &str = file!();
+
+/// Execute an access against `thread_local` [`Regmock`] object, performed by `caller`.
+fn with_mock_from<F, R>(caller: &'static Location<'static>, f: F) -> Result<R, MockError>
+where
+    F: FnOnce(&mut Regmock) -> R,
+{
+    with_mock(|mock| {
+        mock.caller = Some(caller);
+        let result = f(mock);
+        mock.caller = None;
+        result
     })
 }
 
 /// Initialize the thread_local regmock object.
 ///
+/// Replaces a mock set by a previous call. Mocks installed with [`install`]
+/// take precedence while they are in scope.
+///
+/// The replaced mock is verified, see [`Regmock::verify`]. The last mock set
+/// on a thread is only dropped when the thread exits and can't fail the test
+/// then, so tests with expectations or end of test checks should use
+/// [`install`] or [`harness::TestHarness`] instead.
+///
 /// # Panics
 ///
-/// This function will `panic!()` when not being able to initialize the `thread_local`
-/// [`Regmock`] object.
+/// Will panic if the replaced mock fails verification.
 pub fn init_regmock(mock: Arc<Mutex<Regmock>>) {
-    MOCK.with(|m| match m.set(mock) {
-        Ok(_) => {}
-        Err(e) => panic!("Failed to initialize thread_local Regmock with: {:?}", e),
-    })
+    let previous = MOCK.with(|m| m.borrow_mut().base.replace(mock));
+    let Some(previous) = previous else {
+        return;
+    };
+    let Ok(mut previous) = previous.lock() else {
+        return;
+    };
+    if let Err(failures) = previous.verify() {
+        panic!(
+            "Regmock replaced by init_regmock failed verification, the failures \
+             were caused by the code that used the previous mock:\n{}",
+            failures.join("\n")
+        );
+    }
+}
+
+/// Install `mock` as the `thread_local` [`Regmock`] object until the returned
+/// [`MockGuard`] is dropped.
+///
+/// Dropping the guard restores the previously used mock. Installations can be
+/// nested, and the thread can be reused with a fresh mock afterwards.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::{Arc, Mutex};
+/// use regmock_rs::utils::Regmock;
+///
+/// let outer = regmock_rs::install(Arc::new(Mutex::new(Regmock::default())));
+/// regmock_rs::write_fn(0x100, 4, 0x1);
+/// {
+///     let _inner = regmock_rs::install(Arc::new(Mutex::new(Regmock::default())));
+///     assert_eq!(regmock_rs::read_fn(0x100, 4), 0x0);
+/// }
+/// assert_eq!(regmock_rs::read_fn(0x100, 4), 0x1);
+/// assert_eq!(outer.mock().lock().unwrap().log.len_full(), 2);
+/// ```
+pub fn install(mock: Arc<Mutex<Regmock>>) -> MockGuard {
+    let installation = MOCK.with(|m| {
+        let mut m = m.borrow_mut();
+        m.installations += 1;
+        let installation = m.installations;
+        m.scoped.push((installation, mock.clone()));
+        installation
+    });
+    MockGuard {
+        mock,
+        installation,
+        _thread: PhantomData,
+    }
+}
+
+/// Guard returned by [`install`] that uninstalls the mock when dropped.
+///
+/// Dropping the guard also uninstalls all mocks that were installed after it
+/// and are still installed, and verifies the expectations of the mock, see
+/// [`Regmock::verify`]. If the mock was already uninstalled by dropping a
+/// guard of an earlier installation, the installed mocks are left as they are.
+#[must_use = "the mock is uninstalled when the guard is dropped"]
+pub struct MockGuard {
+    mock: Arc<Mutex<Regmock>>,
+    /// Number of the installation, see [`ThreadLocalRegmock::installations`].
+    installation: u64,
+    // the guard refers to the mocks of the thread it was created on
+    _thread: PhantomData<*const ()>,
+}
+
+impl MockGuard {
+    /// Get the installed mock.
+    pub fn mock(&self) -> &Arc<Mutex<Regmock>> {
+        &self.mock
+    }
+}
+
+impl Drop for MockGuard {
+    fn drop(&mut self) {
+        // the thread-local may already be destroyed when the thread exits
+        let _ = MOCK.try_with(|m| {
+            let mut m = m.borrow_mut();
+            let position = m.scoped.iter().position(|(i, _)| *i == self.installation);
+            if let Some(position) = position {
+                m.scoped.truncate(position);
+            }
+        });
+        if std::thread::panicking() {
+            return;
+        }
+        let failures = match self.mock.lock() {
+            Ok(mut mock) => mock.verify(),
+            Err(_) => Ok(()),
+        };
+        if let Err(failures) = failures {
+            panic!("Regmock verification failed:\n{}", failures.join("\n"));
+        }
+    }
 }
 
 /// Disable logging and execution of callbacks during the closure `f`.
@@ -121,6 +275,30 @@ pub fn logging(state: bool) {
     .expect("Could not access regmock thread-local for setting logging state. Most likely your forgot to initialize regmock.")
 }
 
+/// Reset all registers of the `thread_local` MOCK object to their reset values.
+///
+/// See [`Regmock::reset`].
+///
+/// # Panics
+///
+/// Will panic if the thread-local [`Regmock`] object can't be accessed.
+pub fn reset() {
+    with_mock(|mock| mock.reset())
+        .expect("Could not access regmock thread-local for reset. Most likely your forgot to initialize regmock.")
+}
+
+/// Advance the virtual time of the `thread_local` MOCK object by `duration`.
+///
+/// See [`Regmock::advance_time`].
+///
+/// # Panics
+///
+/// Will panic if the thread-local [`Regmock`] object can't be accessed.
+pub fn advance_time(duration: Duration) {
+    with_mock(|mock| mock.advance_time(duration))
+        .expect("Could not access regmock thread-local for advancing time. Most likely your forgot to initialize regmock.")
+}
+
 /// Block until specific register is being polled or timeout occurs.
 ///
 /// `count` specifies the number of consecutive reads to a register that should
@@ -171,19 +349,44 @@ pub fn logs() -> utils::RegmockLog {
     with_mock(|mock| mock.get_logs()).expect("Coudn't get regmock thead-local for getting logs. Most likely your forgot to initialize regmock.")
 }
 
+/// Call the ISRs of all pending and enabled interrupts of the `thread_local`
+/// MOCK object, see [`Regmock::interrupts`].
+///
+/// Called by [`read_fn`] and [`write_fn`] after every register access, so
+/// ISRs run on the thread of the DUT between two register accesses. The
+/// [`Regmock`] is not locked while an ISR runs, so ISRs can access registers
+/// through the PAC.
+///
+/// # Panics
+///
+/// Will panic if the thread-local [`Regmock`] object can't be accessed.
+pub fn dispatch_interrupts() {
+    while let Some((_, isr)) = with_mock(|mock| mock.enter_interrupt())
+        .expect("Could not access regmock thread-local for dispatching interrupts. Most likely your forgot to initialize regmock.")
+    {
+        isr();
+        with_mock(|mock| mock.exit_interrupt())
+            .expect("Could not access regmock thread-local for dispatching interrupts. Most likely your forgot to initialize regmock.");
+    }
+}
+
 /// Perform a read from the mocked registers.
 /// Register this function as the `READ_FN` in the `pacgen` PAC.
 ///
 /// # Panics
 ///
 /// Will panic if the thead-local, [`Regmock`] object can't be accessed.
+#[track_caller]
 pub fn read_fn(reg: usize, len: usize) -> u64 {
-    with_mock(|mock| mock.read_volatile(reg, len)).unwrap_or_else(|e| {
-        panic!(
-            "Cound not `read_volatile(0x{:08X}, {:?})` due to: {:?}",
-            reg, len, e
-        )
-    })
+    let value = with_mock_from(Location::caller(), |mock| mock.read_volatile(reg, len))
+        .unwrap_or_else(|e| {
+            panic!(
+                "Cound not `read_volatile(0x{:08X}, {:?})` due to: {:?}",
+                reg, len, e
+            )
+        });
+    dispatch_interrupts();
+    value
 }
 
 /// Perform a write from the mocked registers.
@@ -193,28 +396,101 @@ pub fn read_fn(reg: usize, len: usize) -> u64 {
 ///
 /// This function calls `panic!()` if the `thead_local`, [`Regmock`] object
 /// cannot be accessed.
+#[track_caller]
 pub fn write_fn(reg: usize, len: usize, value: u64) {
-    with_mock(|mock| mock.write_volatile(reg, len, value)).unwrap_or_else(|e| {
+    with_mock_from(Location::caller(), |mock| {
+        mock.write_volatile(reg, len, value)
+    })
+    .unwrap_or_else(|e| {
         panic!(
             "Cound not `write_volatile(reg: 0x{:08X}, len: {:?}, value: 0x{:08X})` due to: {:?}",
             reg, len, value, e
         )
-    })
+    });
+    dispatch_interrupts();
 }
 
-/// Perform a write from the mocked registers.
-/// Register this function as the `WRITE_FN` in the `pacgen` PAC.
+/// Perform an `LDMST` (load-modify-store) on the mocked registers.
+/// Register this function as the `LDMST` function in the `pacgen` PAC.
+///
+/// `value` holds the data in the lower and the mask in the upper 32 bits,
+/// see [`Regmock::load_modify_store`].
 ///
 /// # Panics
 ///
 /// This function calls `panic!()` if the `thead_local`, [`Regmock`] object
 /// cannot be accessed.
 #[cfg(feature = "aurix")]
-pub fn ldmst_fn(reg: usize, len: usize, value: u64) {
-    with_mock(|mock| mock.load_modify_store(reg, len, value)).unwrap_or_else(|e| {
+#[track_caller]
+pub fn ldmst_fn(reg: usize, value: u64) {
+    with_mock_from(Location::caller(), |mock| {
+        mock.load_modify_store(reg, value)
+    })
+    .unwrap_or_else(|e| {
         panic!(
-            "Cound not `load_modify_store(reg: 0x{:08X}, value: 0x{:08X})` due to: {:?}",
-            reg, len, e
+            "Cound not `load_modify_store(reg: 0x{:08X}, value: 0x{:016X})` due to: {:?}",
+            reg, value, e
         )
-    })
+    });
+    dispatch_interrupts();
+}
+
+/// Perform a `SWAP.W` on the mocked registers, see [`Regmock::swap`].
+///
+/// # Panics
+///
+/// This function calls `panic!()` if the `thead_local`, [`Regmock`] object
+/// cannot be accessed.
+#[cfg(feature = "aurix")]
+#[track_caller]
+pub fn swap_fn(reg: usize, value: u64) -> u64 {
+    let old =
+        with_mock_from(Location::caller(), |mock| mock.swap(reg, value)).unwrap_or_else(|e| {
+            panic!(
+                "Cound not `swap(reg: 0x{:08X}, value: 0x{:016X})` due to: {:?}",
+                reg, value, e
+            )
+        });
+    dispatch_interrupts();
+    old
+}
+
+/// Perform a `SWAPMSK.W` on the mocked registers, see [`Regmock::swap_masked`].
+///
+/// # Panics
+///
+/// This function calls `panic!()` if the `thead_local`, [`Regmock`] object
+/// cannot be accessed.
+#[cfg(feature = "aurix")]
+#[track_caller]
+pub fn swapmsk_fn(reg: usize, value: u64) -> u64 {
+    let old = with_mock_from(Location::caller(), |mock| mock.swap_masked(reg, value))
+        .unwrap_or_else(|e| {
+            panic!(
+                "Cound not `swap_masked(reg: 0x{:08X}, value: 0x{:016X})` due to: {:?}",
+                reg, value, e
+            )
+        });
+    dispatch_interrupts();
+    old
+}
+
+/// Perform a `CMPSWAP.W` on the mocked registers, see [`Regmock::compare_and_swap`].
+///
+/// # Panics
+///
+/// This function calls `panic!()` if the `thead_local`, [`Regmock`] object
+/// cannot be accessed.
+#[cfg(feature = "aurix")]
+#[track_caller]
+pub fn cmpswap_fn(reg: usize, value: u64) -> u64 {
+    let old = with_mock_from(Location::caller(), |mock| mock.compare_and_swap(reg, value))
+        .unwrap_or_else(|e| {
+            panic!(
+                "Cound not `compare_and_swap(reg: 0x{:08X}, value: 0x{:016X})` due to: {:?}",
+                reg, value, e
+            )
+        });
+    dispatch_interrupts();
+    old
 }
@@ -0,0 +1,262 @@
+//! Stable file format for recorded access logs, used for golden-file testing.
+//!
+//! A [`LogFile`] holds the run-length-encoded entries of a [`RegmockLog`]
+//! together with the register names. It only contains the deterministic
+//! parts of the accesses, so recording the same test twice produces the same
+//! file. It can be stored as JSON and loaded again, or exported as CSV to be
+//! reviewed in diffs.
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::utils::{RegisterAccess, RegisterAccessType, Regmock, RegmockLog};
+
+/// Version of the file format written by [`LogFile`].
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Errors generated when loading a [`LogFile`] or a sequence of accesses.
+#[derive(Debug, Clone)]
+pub enum LogFileError {
+    /// The file could not be read or written.
+    Io(String),
+    /// The file is not valid JSON or does not match the format.
+    Json(String),
+    /// The file was written by a newer, unsupported version of the format.
+    Version(u32),
+}
+
+impl From<LogFileError> for String {
+    fn from(value: LogFileError) -> Self {
+        format!("failed to load log due to: {:?}", value)
+    }
+}
+
+impl From<serde_json::Error> for LogFileError {
+    fn from(value: serde_json::Error) -> Self {
+        LogFileError::Json(value.to_string())
+    }
+}
+
+/// Recorded access log in a stable format, see the [module](self) documentation.
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::logfile::LogFile;
+/// use regmock_rs::utils::Regmock;
+///
+/// let mut mock = Regmock::default();
+/// mock.write_volatile(0x100, 4, 0x1);
+/// let json = mock.log_file().to_json().unwrap();
+///
+/// let golden = LogFile::from_json(&json).unwrap();
+/// assert_eq!(golden, mock.log_file());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFile {
+    /// Version of the file format, see [`FORMAT_VERSION`].
+    pub version: u32,
+    /// Run-length-encoded entries of the log.
+    pub entries: Vec<LogEntry>,
+}
+
+/// Entry of a [`LogFile`], corresponds to an entry of [`RegmockLog::log`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogEntry {
+    /// Position of the access in the log, see [`RegisterAccess::seq`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seq: Option<u64>,
+    /// Virtual time of the access in nanoseconds.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub time_ns: Option<u64>,
+    /// Type of the access.
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub ty: Option<RegisterAccessType>,
+    /// Address of the accessed register.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub addr: Option<usize>,
+    /// Name of the accessed register, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Length of the access in bytes.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub len: Option<usize>,
+    /// Value of the register before the access.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub before: Option<u64>,
+    /// Value of the register after the access.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub after: Option<u64>,
+    /// Bits modified by a masked access.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<u64>,
+    /// Number of consecutive identical **`READ`**s the entry stands for.
+    pub count: usize,
+}
+
+impl LogEntry {
+    fn new(access: &RegisterAccess, count: usize, name: Option<String>) -> Self {
+        Self {
+            seq: access.seq,
+            time_ns: access.time.map(|t| t.as_nanos() as u64),
+            ty: access.ty.clone(),
+            addr: access.addr,
+            name,
+            len: access.len,
+            before: access.before,
+            after: access.after,
+            mask: access.mask,
+            count,
+        }
+    }
+
+    /// Get the recorded access. The register name is not part of a [`RegisterAccess`].
+    pub fn access(&self) -> RegisterAccess {
+        RegisterAccess {
+            ty: self.ty.clone(),
+            addr: self.addr,
+            len: self.len,
+            before: self.before,
+            after: self.after,
+            time: self.time_ns.map(Duration::from_nanos),
+            mask: self.mask,
+            seq: self.seq,
+            ..Default::default()
+        }
+    }
+}
+
+impl LogFile {
+    /// Construct a [`LogFile`] from `log`, naming registers with `name`.
+    fn new(log: &RegmockLog, name: impl Fn(usize) -> Option<String>) -> Self {
+        Self {
+            version: FORMAT_VERSION,
+            entries: log
+                .log
+                .iter()
+                .map(|(access, count)| LogEntry::new(access, *count, access.addr.and_then(&name)))
+                .collect(),
+        }
+    }
+
+    /// Serialize the log as pretty-printed JSON.
+    pub fn to_json(&self) -> Result<String, LogFileError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Deserialize a log from JSON.
+    pub fn from_json(json: &str) -> Result<Self, LogFileError> {
+        let file: Self = serde_json::from_str(json)?;
+        if file.version > FORMAT_VERSION {
+            return Err(LogFileError::Version(file.version));
+        }
+        Ok(file)
+    }
+
+    /// Write the log as JSON to the file at `path`.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), LogFileError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_json()? + "\n")
+            .map_err(|e| LogFileError::Io(format!("{}: {}", path.display(), e)))
+    }
+
+    /// Load a log from the JSON file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LogFileError> {
+        let path = path.as_ref();
+        let json = std::fs::read_to_string(path)
+            .map_err(|e| LogFileError::Io(format!("{}: {}", path.display(), e)))?;
+        Self::from_json(&json)
+    }
+
+    /// Get the recorded log, e.g. to run matchers against it.
+    pub fn to_log(&self) -> RegmockLog {
+        RegmockLog {
+            log: self
+                .entries
+                .iter()
+                .map(|entry| (entry.access(), entry.count))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// Write the log as CSV to `out`, one line per entry with a header line.
+    ///
+    /// Addresses and values are written as hexadecimal numbers, missing
+    /// values as empty columns.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.write_volatile(0x100, 4, 0x1);
+    /// let mut csv = Vec::new();
+    /// mock.log_file().write_csv(&mut csv).unwrap();
+    /// assert_eq!(
+    ///     String::from_utf8(csv).unwrap(),
+    ///     "seq,time_ns,type,addr,name,len,before,after,mask,count\n\
+    ///      0,0,write,0x00000100,,4,0x00000000,0x00000001,,1\n"
+    /// );
+    /// ```
+    pub fn write_csv(&self, mut out: impl Write) -> io::Result<()> {
+        fn hex(value: Option<impl Into<u64>>) -> String {
+            value.map_or_else(String::new, |v| format!("0x{:08X}", v.into()))
+        }
+        fn num(value: Option<impl ToString>) -> String {
+            value.map_or_else(String::new, |v| v.to_string())
+        }
+
+        writeln!(
+            out,
+            "seq,time_ns,type,addr,name,len,before,after,mask,count"
+        )?;
+        for entry in &self.entries {
+            let ty = match &entry.ty {
+                Some(ty) => format!("{ty:?}").to_lowercase(),
+                None => String::new(),
+            };
+            writeln!(
+                out,
+                "{},{},{ty},{},{},{},{},{},{},{}",
+                num(entry.seq),
+                num(entry.time_ns),
+                hex(entry.addr.map(|a| a as u64)),
+                csv_field(entry.name.as_deref().unwrap_or_default()),
+                num(entry.len),
+                hex(entry.before),
+                hex(entry.after),
+                hex(entry.mask),
+                entry.count,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Quote `field` if it contains characters with a special meaning in CSV.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+impl From<&RegmockLog> for LogFile {
+    /// Construct a [`LogFile`] without register names.
+    fn from(log: &RegmockLog) -> Self {
+        Self::new(log, |_| None)
+    }
+}
+
+impl Regmock {
+    /// Get the [`log`](#structfield.log) as [`LogFile`], with the register
+    /// names from the [`database`](#structfield.database) or the name resolver.
+    pub fn log_file(&self) -> LogFile {
+        LogFile::new(&self.log, |addr| self.get_reg_name(addr).map(str::to_owned))
+    }
+}
@@ -0,0 +1,230 @@
+//! Matchers that combine other matchers.
+//!
+//! The combined matchers are run against clones of the log, so the log has
+//! to be a cloneable iterator like [`RegmockLog::iter_full`]. Failures of the
+//! combined matchers are reported as [`MatchError::causes`].
+
+use super::{LogMatcher, MatchError};
+use crate::utils::*;
+
+/// Verify that all matchers of a tuple match the log.
+///
+/// Fails with the errors of all failed matchers, see [`all_of`].
+#[derive(Clone)]
+pub struct AllOf<M>(pub M);
+
+/// Verify that at least one matcher of a tuple matches the log.
+///
+/// Fails with the errors of all matchers if none of them matches, see [`any_of`].
+#[derive(Clone)]
+pub struct AnyOf<M>(pub M);
+
+/// Verify that a matcher does **not** match the log, see [`not`].
+#[derive(Clone)]
+pub struct Not<M>(pub M);
+
+/// Verify that the log starts with accesses matched by
+/// [`first`](#structfield.first), followed by accesses matched by
+/// [`second`](#structfield.second), see [`then`].
+///
+/// The log is split where `first` stops matching, the remaining accesses
+/// have to match `second`. The sequence matchers stop after the last expected
+/// access, other matchers after the longest beginning of the log they match
+/// without a shorter beginning in between that they don't match, see
+/// [`LogMatcher::match_prefix`].
+#[derive(Clone)]
+pub struct Then<A, B> {
+    /// Matcher of the beginning of the log.
+    pub first: A,
+    /// Matcher of the accesses after the beginning matched by `first`.
+    pub second: B,
+}
+
+/// Construct an [`AllOf`] matcher from a tuple of up to 6 matchers.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// given!(
+///     regmock_rs::logs().iter_full(),
+///     all_of((
+///         require_reg!(pac::SPI.ctrl(), written_once),
+///         require_reg!(pac::SPI.ctrl(), all_writes_before_writes_to(pac::SPI.data())),
+///     ))
+/// );
+/// ```
+pub fn all_of<M>(matchers: M) -> AllOf<M> {
+    AllOf(matchers)
+}
+
+/// Construct an [`AnyOf`] matcher from a tuple of up to 6 matchers.
+pub fn any_of<M>(matchers: M) -> AnyOf<M> {
+    AnyOf(matchers)
+}
+
+/// Construct a [`Not`] matcher that negates `matcher`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// given!(full_log, not(require_reg!(pac::SPI.ctrl(), not_written)));
+/// ```
+pub fn not<M>(matcher: M) -> Not<M> {
+    Not(matcher)
+}
+
+/// Construct a [`Then`] matcher that matches `first` and then `second`.
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// given!(
+///     full_log,
+///     then(
+///         require_subseq!(vec![&enable]),
+///         require_reg!(pac::SPI.ctrl(), not_written)
+///     )
+/// );
+/// ```
+pub fn then<A, B>(first: A, second: B) -> Then<A, B> {
+    Then { first, second }
+}
+
+impl<M> AllOf<M> {
+    const NAME: &'static str = "AllOf";
+}
+
+impl<M> AnyOf<M> {
+    const NAME: &'static str = "AnyOf";
+}
+
+/// Implement [`AllOf`] and [`AnyOf`] for tuples of matchers.
+macro_rules! tuple_combinators {
+    ($($m:ident),+) => {
+        impl<'log, T, $($m),+> LogMatcher<'log, T> for AllOf<($($m,)+)>
+        where
+            T: IntoIterator<Item = &'log RegisterAccess> + Clone,
+            $($m: LogMatcher<'log, T>),+
+        {
+            fn name(&self) -> &'static str {
+                Self::NAME
+            }
+
+            /// Match all matchers against clones of the log.
+            #[allow(non_snake_case)]
+            fn r#match(self, log: T) -> Result<(), MatchError> {
+                let ($($m,)+) = self.0;
+                let results = [$($m.r#match(log.clone())),+];
+                let count = results.len();
+                let causes: Vec<_> = results.into_iter().filter_map(Result::err).collect();
+                if causes.is_empty() {
+                    Ok(())
+                } else {
+                    MatchError::nested(
+                        Self::NAME,
+                        format!("{} of {count} matchers failed", causes.len()),
+                        causes,
+                    )
+                }
+            }
+        }
+
+        impl<'log, T, $($m),+> LogMatcher<'log, T> for AnyOf<($($m,)+)>
+        where
+            T: IntoIterator<Item = &'log RegisterAccess> + Clone,
+            $($m: LogMatcher<'log, T>),+
+        {
+            fn name(&self) -> &'static str {
+                Self::NAME
+            }
+
+            /// Match the matchers against clones of the log until one of them matches.
+            #[allow(non_snake_case)]
+            fn r#match(self, log: T) -> Result<(), MatchError> {
+                let ($($m,)+) = self.0;
+                let mut causes = Vec::new();
+                $(
+                    match $m.r#match(log.clone()) {
+                        Ok(()) => return Ok(()),
+                        Err(error) => causes.push(error),
+                    }
+                )+
+                MatchError::nested(
+                    Self::NAME,
+                    format!("none of the {} matchers matched", causes.len()),
+                    causes,
+                )
+            }
+        }
+    };
+}
+
+tuple_combinators!(A, B);
+tuple_combinators!(A, B, C);
+tuple_combinators!(A, B, C, D);
+tuple_combinators!(A, B, C, D, E);
+tuple_combinators!(A, B, C, D, E, F);
+
+impl<M> Not<M> {
+    const NAME: &'static str = "Not";
+}
+
+impl<'log, T, M> LogMatcher<'log, T> for Not<M>
+where
+    T: IntoIterator<Item = &'log RegisterAccess>,
+    M: LogMatcher<'log, T>,
+{
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Match [`Not`] against log of [`RegisterAccess`]'s.
+    fn r#match(self, log: T) -> Result<(), MatchError> {
+        let name = self.0.name();
+        match self.0.r#match(log) {
+            Ok(()) => MatchError::error(
+                Self::NAME,
+                format!("{name} matched, but was expected to fail"),
+            ),
+            Err(_) => Ok(()),
+        }
+    }
+}
+
+impl<A, B> Then<A, B> {
+    const NAME: &'static str = "Then";
+}
+
+impl<'log, T, A, B> LogMatcher<'log, T> for Then<A, B>
+where
+    T: IntoIterator<Item = &'log RegisterAccess>,
+    A: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    B: LogMatcher<'log, Vec<&'log RegisterAccess>>,
+{
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Match [`Then`] against log of [`RegisterAccess`]'s.
+    fn r#match(self, log: T) -> Result<(), MatchError> {
+        let log: Vec<_> = log.into_iter().collect();
+        let split = match LogMatcher::<'log, Vec<_>>::match_prefix(self.first, &log) {
+            Ok(split) => split,
+            Err(cause) => {
+                return MatchError::nested(
+                    Self::NAME,
+                    "first matcher does not match any beginning of the log".to_string(),
+                    vec![cause],
+                )
+            }
+        };
+        match self.second.r#match(log[split..].to_vec()) {
+            Ok(()) => Ok(()),
+            Err(error) => MatchError::nested(
+                Self::NAME,
+                format!("second matcher failed on the log after the first {split} accesses"),
+                vec![error],
+            ),
+        }
+    }
+}
@@ -12,6 +12,8 @@
 ///   - arbitrary iterater of register accesses, like `regmock_rs::log().iter()`, can be filtered etc.
 ///   - `full_log` to match against complete log with duplicate accesses unrolled
 ///   - `skip_log` to match against log with read accesses compressed (single entry for polling)
+///   - `thread_log(id)` to match against the complete log of the accesses performed
+///     by the thread with the [`ThreadId`](std::thread::ThreadId) `id`
 ///
 /// # Examples
 ///
@@ -48,7 +50,7 @@ macro_rules! given {
         match m.r#match(regmock_rs::logs().iter_full()) {
             Ok(_) => ..,
             Err(me) => {
-                panic!("\nFailed to match {} because:\n'{}'", me.name, me.reason);
+                panic!("\nFailed to match {me}");
             }
         }
     }};
@@ -58,7 +60,17 @@ macro_rules! given {
         match m.r#match(regmock_rs::logs().iter()) {
             Ok(_) => ..,
             Err(me) => {
-                panic!("\nFailed to match {} because:\n'{}'", me.name, me.reason);
+                panic!("\nFailed to match {me}");
+            }
+        }
+    }};
+    (thread_log($thread: expr), $matcher: expr) => {{
+        use regmock_rs::matchers::*;
+        let mut m = $matcher;
+        match m.r#match(regmock_rs::logs().iter_thread($thread)) {
+            Ok(_) => ..,
+            Err(me) => {
+                panic!("\nFailed to match {me}");
             }
         }
     }};
@@ -69,7 +81,7 @@ macro_rules! given {
         match m.r#match($log) {
             Ok(_) => ..,
             Err(me) => {
-                panic!("\nFailed to match {} because:\n'{}'", me.name, me.reason);
+                panic!("\nFailed to match {me}");
             }
         }
     }};
@@ -188,3 +200,48 @@ macro_rules! require_seq {
         regmock_rs::matchers::LogSequenceMatcher::new($seq)
     };
 }
+
+/// Macro for constructing a [`LogSubsequenceMatcher`](crate::matchers::LogSubsequenceMatcher):
+/// the accesses have to happen in order, other accesses in between are ignored.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// given!(full_log, require_subseq!(vec![&w0, &w1]));
+/// ```
+#[macro_export]
+macro_rules! require_subseq {
+    ($seq:expr) => {
+        regmock_rs::matchers::LogSubsequenceMatcher::new($seq)
+    };
+}
+
+/// Macro for constructing a [`LogUnorderedMatcher`](crate::matchers::LogUnorderedMatcher):
+/// the accesses have to happen in any order, other accesses are ignored.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// given!(full_log, require_unordered!(vec![&w1, &w0]));
+/// ```
+#[macro_export]
+macro_rules! require_unordered {
+    ($set:expr) => {
+        regmock_rs::matchers::LogUnorderedMatcher::new($set)
+    };
+}
+
+/// Macro for constructing a [`LogWindowMatcher`](crate::matchers::LogWindowMatcher):
+/// the accesses have to happen in order without other accesses in between.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// given!(full_log, require_window!(vec![&r0, &w0]));
+/// ```
+#[macro_export]
+macro_rules! require_window {
+    ($window:expr) => {
+        regmock_rs::matchers::LogWindowMatcher::new($window)
+    };
+}
@@ -1,26 +1,65 @@
 //! Collection of matchers that can be run against
 //! iterators that yield [`RegisterAccess`].
+//!
+//! Matchers that check writes to a register consider all accesses that
+//! write the register, see [`RegisterAccess::is_write`].
 
 use crate::utils::RegisterAccessType::*;
 use crate::utils::*;
-use crate::with_mock;
+use crate::{with_mock, MockError};
 use itertools::Diff;
 use itertools::Itertools;
+use std::cell::RefCell;
 
+mod combinators;
 mod macros;
 
+pub use combinators::*;
+
 /// Error produced by matchers.
+///
+/// Errors of combined matchers hold the errors of the matchers they combine
+/// in [`causes`](#structfield.causes), forming a tree.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MatchError {
     /// Name of the matcher
     pub name: &'static str,
     /// Reason why the matcher failed.
     pub reason: String,
+    /// Errors of the nested matchers that caused the failure.
+    pub causes: Vec<MatchError>,
 }
 
 impl MatchError {
     /// Construct new error result
     pub fn error(name: &'static str, reason: String) -> Result<(), MatchError> {
-        Err(MatchError { name, reason })
+        Self::nested(name, reason, Vec::new())
+    }
+
+    /// Construct new error result caused by the errors of nested matchers.
+    pub fn nested(
+        name: &'static str,
+        reason: String,
+        causes: Vec<MatchError>,
+    ) -> Result<(), MatchError> {
+        Err(MatchError {
+            name,
+            reason,
+            causes,
+        })
+    }
+}
+
+impl std::fmt::Display for MatchError {
+    /// Render the error and its causes, each nesting level indented by two spaces.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} because:\n'{}'", self.name, self.reason)?;
+        for cause in &self.causes {
+            for line in cause.to_string().lines() {
+                write!(f, "\n  {line}")?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -37,6 +76,83 @@ pub trait LogMatcher<'log, T: IntoIterator<Item = &'log RegisterAccess>> {
     /// Consumes and matches `self` against some sequence of [`RegisterAccess`]'s.
     ///
     fn r#match(self, log: T) -> Result<(), MatchError>;
+
+    /// Name of the matcher, used in the [`MatchError`]s of combined matchers.
+    fn name(&self) -> &'static str {
+        "LogMatcher"
+    }
+
+    /// Match `self` against the beginning of `log` and return the number of
+    /// accesses it matched, used by [`then`] to split the log.
+    ///
+    /// The sequence matchers match up to the last expected access. The default
+    /// implementation extends the beginning of the log one access at a time
+    /// and stops where `self` stops matching, e.g. a matcher of a register
+    /// that was written once matches up to the second write. It matches once
+    /// per access, so matchers used with [`then`] on long logs should find the
+    /// end in a single pass like the built-in matchers do.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let mut matched = None;
+        for end in 0..=log.len() {
+            let result = LogMatcher::<'log, Vec<_>>::r#match(self.clone(), log[..end].to_vec());
+            match (result, matched) {
+                (Ok(()), _) => matched = Some(end),
+                (Err(_), Some(matched)) => return Ok(matched),
+                (Err(error), None) if end == log.len() => return Err(error),
+                (Err(_), None) => {}
+            }
+        }
+        Ok(log.len())
+    }
+}
+
+/// Match the shortest beginning of `log` that `matcher` matches.
+///
+/// Only for matchers that keep matching once they matched a beginning of the
+/// log, which allows a binary search.
+fn match_shortest_prefix<'log, M>(
+    matcher: M,
+    log: &[&'log RegisterAccess],
+) -> Result<usize, MatchError>
+where
+    M: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+{
+    matcher.clone().r#match(log.to_vec())?;
+    // `log[..high]` is matched, `log[..low - 1]` is not
+    let (mut low, mut high) = (0, log.len());
+    while low < high {
+        let mid = (low + high) / 2;
+        if matcher.clone().r#match(log[..mid].to_vec()).is_ok() {
+            high = mid;
+        } else {
+            low = mid + 1;
+        }
+    }
+    Ok(high)
+}
+
+/// Match the beginning of `log` up to `end`, or the whole log if `end` is `None`.
+fn match_up_to<'log, M>(
+    matcher: M,
+    log: &[&'log RegisterAccess],
+    end: Option<usize>,
+) -> Result<usize, MatchError>
+where
+    M: LogMatcher<'log, Vec<&'log RegisterAccess>>,
+{
+    let end = end.unwrap_or(log.len());
+    matcher.r#match(log[..end].to_vec()).map(|()| end)
+}
+
+/// Indices of the writes to the register at `addr` in `log`.
+fn writes_to<'a>(log: &'a [&RegisterAccess], addr: usize) -> impl Iterator<Item = usize> + 'a {
+    log.iter()
+        .enumerate()
+        .filter(move |(_, access)| access.is_write() && access.addr == Some(addr))
+        .map(|(index, _)| index)
 }
 
 /// Match an *exact* sequence of values written to a specific register.
@@ -63,6 +179,7 @@ pub trait LogMatcher<'log, T: IntoIterator<Item = &'log RegisterAccess>> {
 ///     .r#match(crate::get_logs().iter());
 /// ```
 ///
+#[derive(Clone)]
 pub struct ValuesWrittenAre {
     /// Address of the target register.
     address: usize,
@@ -89,6 +206,10 @@ pub fn new<T, I>(address: usize, write_sequence: I) -> Self
 }
 
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T> for ValuesWrittenAre {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Verify that a given sequence of values was written to register.
     ///
     /// Will succeed if the values in [`self.write_sequence`] are written
@@ -99,9 +220,7 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             .into_iter()
             .filter(|r| {
                 r.addr.as_ref().is_some_and(|addr| addr == &self.address)
-                    && r.ty
-                        .as_ref()
-                        .is_some_and(|ty| ty == &RegisterAccessType::WRITE)
+                    && r.is_write()
                     && r.after.as_ref().is_some()
             })
             .map(|m| m.after.unwrap())
@@ -141,6 +260,15 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             Ok(())
         }
     }
+
+    /// Match the beginning of the log up to the write after the expected writes.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let end = writes_to(log, self.address).nth(self.write_sequence.len());
+        match_up_to(self, log, end)
+    }
 }
 
 /// Verify that register was at least written to once, before other register was written to.
@@ -148,6 +276,7 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
 /// Will succeed if at least one write to [`target`](#structfield.target) happened before the first
 /// write to [`other`](#structfield.other).
 /// Any additional writes do not affect the result. Reads are ignored.
+#[derive(Clone)]
 pub struct WrittenToBeforeWriteTo {
     /// Address of the target register.
     pub target: usize,
@@ -166,10 +295,14 @@ pub fn new(target: usize, other: usize) -> Self {
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T>
     for WrittenToBeforeWriteTo
 {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`WrittenToBeforeWriteTo`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         let mut filtered = log.into_iter().filter(|access| {
-            access.ty.as_ref().is_some_and(|ty| *ty == WRITE)
+            access.is_write()
                 && access
                     .addr
                     .as_ref()
@@ -198,6 +331,15 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             ),
         }
     }
+
+    /// Match the whole log, once the other register was written after the
+    /// target register, more writes don't change the result.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        match_up_to(self, log, None)
+    }
 }
 
 /// Verify that all writes to register happened before any write to other register.
@@ -206,6 +348,7 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
 /// first write (0..1) to [`other`](#structfield.other).
 /// Other registers and reads are ignored. Matcher succeeds if there are no writes to either
 /// register.
+#[derive(Clone)]
 pub struct AllWritesBeforeWritesTo {
     /// Register whose writes must **all** happen before writing [`other`](#structfield.other)
     pub target: usize,
@@ -224,10 +367,14 @@ pub fn new(target: usize, other: usize) -> Self {
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T>
     for AllWritesBeforeWritesTo
 {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`AllWritesBeforeWritesTo`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         let filtered = log.into_iter().filter(|access| {
-            access.ty.as_ref().is_some_and(|ty| *ty == WRITE)
+            access.is_write()
                 && access
                     .addr
                     .as_ref()
@@ -250,12 +397,31 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             MatchError::error(Self::NAME,format!("Target register: {target_id} was written to after write to other register: {other_id}"))
         }
     }
+
+    /// Match the beginning of the log up to the first write to the target
+    /// register after a write to the other register.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let mut other_written = false;
+        let end = log.iter().position(|access| match access.addr {
+            Some(addr) if access.is_write() && addr == self.target => other_written,
+            Some(addr) if access.is_write() && addr == self.other => {
+                other_written = true;
+                false
+            }
+            _ => false,
+        });
+        match_up_to(self, log, end)
+    }
 }
 
 /// Verify that register was written **exactly** once.
 ///
 /// Will succeed [`target`](#structfield.target) is written to exactly once.
 /// Reads are ignored.
+#[derive(Clone)]
 pub struct WrittenOnceMatcher {
     /// Register which must only be written once.
     pub target: usize,
@@ -272,12 +438,16 @@ pub fn new(target: usize) -> Self {
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T>
     for WrittenOnceMatcher
 {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`WrittenOnceMatcher`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         match log
             .into_iter()
             .filter(|access| {
-                access.ty.as_ref().is_some_and(|ty| *ty == WRITE)
+                access.is_write()
                     && access
                         .addr
                         .as_ref()
@@ -295,12 +465,22 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             ),
         }
     }
+
+    /// Match the beginning of the log up to the second write.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let end = writes_to(log, self.target).nth(1);
+        match_up_to(self, log, end)
+    }
 }
 
 /// Verify that register was **never** written.
 ///
 /// Will succeed [`self.target`](#structfield.target) is never written to.
 /// Reads are ignored.
+#[derive(Clone)]
 pub struct NotWrittenMatcher {
     /// Register which must not be written to.
     pub target: usize,
@@ -315,12 +495,16 @@ pub fn new(target: usize) -> Self {
 }
 
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T> for NotWrittenMatcher {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`NotWrittenMatcher`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         match log
             .into_iter()
             .filter(|access| {
-                access.ty.as_ref().is_some_and(|ty| *ty == WRITE)
+                access.is_write()
                     && access
                         .addr
                         .as_ref()
@@ -338,12 +522,22 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             ),
         }
     }
+
+    /// Match the beginning of the log up to the first write.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let end = writes_to(log, self.target).next();
+        match_up_to(self, log, end)
+    }
 }
 
 /// Verify that last access to a register was a read.
 ///
 /// Will succeed [`target`](#structfield.target) was read, and no access happened afterwards.
 /// Other registers are ignored.
+#[derive(Clone)]
 pub struct ReadLastMatcher {
     /// Register which must not be written to.
     pub target: usize,
@@ -358,6 +552,10 @@ pub fn new(target: usize) -> Self {
 }
 
 impl<'log, T: IntoIterator<Item = &'log RegisterAccess>> LogMatcher<'log, T> for ReadLastMatcher {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`ReadLastMatcher`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         match log
@@ -390,12 +588,30 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             ),
         }
     }
+
+    /// Match the beginning of the log up to the first access other than a
+    /// read after a read of the register.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let is_read = |access: &RegisterAccess| access.ty.as_ref().is_some_and(|ty| *ty == READ);
+        let end = log
+            .iter()
+            .enumerate()
+            .filter(|(_, access)| access.addr == Some(self.target))
+            .skip_while(|(_, access)| !is_read(access))
+            .find(|(_, access)| !is_read(access))
+            .map(|(index, _)| index);
+        match_up_to(self, log, end)
+    }
 }
 
 /// Verify a sequence of [`RegisterAccess`]'s happened.
 ///
 /// Will succeed if [`seq`](#structfield.seq) yields equal [`RegisterAccess`]'s
 /// as the provided log iterator. Fails is the iterators are not pairwise equal.
+#[derive(Clone)]
 pub struct LogSequenceMatcher<'seq, SEQ>
 where
     SEQ: IntoIterator<Item = &'seq RegisterAccess>,
@@ -419,6 +635,10 @@ impl<'seq, 'log, SEQ, T> LogMatcher<'log, T> for LogSequenceMatcher<'seq, SEQ>
     SEQ: IntoIterator<Item = &'seq RegisterAccess>,
     T: IntoIterator<Item = &'log RegisterAccess>,
 {
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
     /// Match [`LogSequenceMatcher`] against log of [`RegisterAccess`]'s.
     fn r#match(self, log: T) -> Result<(), MatchError> {
         if let Some(diff) =
@@ -429,21 +649,21 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
                 match diff {
                     Diff::FirstMismatch(index, mut actual_rem, mut expected_rem) => {
                         format!(
-                            "Actual register accesses differ from expected accesses at index:{index} with\nexpected: {:?}\nactual:   {:?}",
-                            expected_rem.next().unwrap(),
-                            actual_rem.next().unwrap()
+                            "Actual register accesses differ from expected accesses at index:{index} with\nexpected: {}\nactual:   {}",
+                            access_id(expected_rem.next().unwrap()),
+                            access_id(actual_rem.next().unwrap())
                         )
                     }
                     Diff::Shorter(iter_count, actual_rem) => {
                         format!(
                         "Found more accesses than expected. Expected {iter_count} writes.\nValues of the surplus accesses are:\n{}",
-                        actual_rem.map(|a| format!("{:?}",a)).collect_vec().join("\n")
+                        actual_rem.map(access_id).collect_vec().join("\n")
                         )
                     }
                     Diff::Longer(iter_count, expected_rem) => {
                         format!(
                         "Expected more accesse. Only {iter_count} accesses were recorded.\nValues of the remaining expected accesses are:\n{}",
-                        expected_rem.map(|e| format!("{:?}",e)).collect_vec().join("\n")
+                        expected_rem.map(access_id).collect_vec().join("\n")
                         )
                     }
                 },
@@ -452,14 +672,332 @@ fn r#match(self, log: T) -> Result<(), MatchError> {
             Ok(())
         }
     }
+
+    /// Match the beginning of the log as long as the expected sequence.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let len = self.clone().seq.into_iter().count().min(log.len());
+        match_up_to(self, log, Some(len))
+    }
+}
+
+/// Verify that a sequence of [`RegisterAccess`]'s happened in order, with
+/// any other accesses in between.
+///
+/// Will succeed if every access of [`seq`](#structfield.seq) is equal to an
+/// access of the log, in the same order. Unlike [`LogSequenceMatcher`],
+/// accesses of the log that are not expected are ignored.
+#[derive(Clone)]
+pub struct LogSubsequenceMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    pub seq: SEQ,
+}
+
+impl<'seq, SEQ> LogSubsequenceMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    const NAME: &'static str = "LogSubsequenceMatcher";
+    /// Construct new [`LogSubsequenceMatcher`]
+    pub fn new(seq: SEQ) -> Self {
+        Self { seq }
+    }
+}
+
+impl<'seq, 'log, SEQ, T> LogMatcher<'log, T> for LogSubsequenceMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+    T: IntoIterator<Item = &'log RegisterAccess>,
+{
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Match [`LogSubsequenceMatcher`] against log of [`RegisterAccess`]'s.
+    fn r#match(self, log: T) -> Result<(), MatchError> {
+        let expected = self.seq.into_iter().collect_vec();
+        let mut found = 0;
+        let mut last_match = None;
+        for (index, actual) in log.into_iter().enumerate() {
+            if found == expected.len() {
+                break;
+            }
+            if expected[found].eq(actual) {
+                found += 1;
+                last_match = Some((index, actual));
+            }
+        }
+        if found == expected.len() {
+            return Ok(());
+        }
+        let stopped = match last_match {
+            Some((index, access)) => format!("after log index {index}: {}", access_id(access)),
+            None => "at the start of the log".to_string(),
+        };
+        MatchError::error(
+            Self::NAME,
+            format!(
+                "Found {found} of {} expected accesses in order, matching stopped {stopped}\nExpected accesses not found:\n{}",
+                expected.len(),
+                expected[found..].iter().map(|e| access_id(e)).join("\n")
+            ),
+        )
+    }
+
+    /// Match the shortest beginning of the log that contains the expected accesses.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        match_shortest_prefix(self, log)
+    }
+}
+
+/// Verify that a set of [`RegisterAccess`]'s happened in any order.
+///
+/// Will succeed if every access of [`set`](#structfield.set) is equal to a
+/// different access of the log. The order of the accesses and accesses of
+/// the log that are not expected are ignored.
+#[derive(Clone)]
+pub struct LogUnorderedMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    pub set: SEQ,
+}
+
+impl<'seq, SEQ> LogUnorderedMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    const NAME: &'static str = "LogUnorderedMatcher";
+    /// Construct new [`LogUnorderedMatcher`]
+    pub fn new(set: SEQ) -> Self {
+        Self { set }
+    }
+}
+
+impl<'seq, 'log, SEQ, T> LogMatcher<'log, T> for LogUnorderedMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+    T: IntoIterator<Item = &'log RegisterAccess>,
+{
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Match [`LogUnorderedMatcher`] against log of [`RegisterAccess`]'s.
+    fn r#match(self, log: T) -> Result<(), MatchError> {
+        let expected = self.set.into_iter().collect_vec();
+        let log = log.into_iter().collect_vec();
+        // expected accesses can match several log accesses, so the
+        // assignment is a bipartite matching found with augmenting paths
+        let candidates = expected
+            .iter()
+            .map(|e| (0..log.len()).filter(|i| e.eq(&log[*i])).collect_vec())
+            .collect_vec();
+        let mut assigned: Vec<Option<usize>> = vec![None; log.len()];
+        let unmatched = (0..expected.len())
+            .filter(|e| !augment(*e, &candidates, &mut assigned, &mut vec![false; log.len()]))
+            .collect_vec();
+        if unmatched.is_empty() {
+            return Ok(());
+        }
+        MatchError::error(
+            Self::NAME,
+            format!(
+                "{} of {} expected accesses were not found in the log of {} accesses:\n{}",
+                unmatched.len(),
+                expected.len(),
+                log.len(),
+                unmatched.iter().map(|e| access_id(expected[*e])).join("\n")
+            ),
+        )
+    }
+
+    /// Match the shortest beginning of the log that contains the expected accesses.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        match_shortest_prefix(self, log)
+    }
+}
+
+/// Assign the expected access `e` to one of its `candidates`, reassigning
+/// other expected accesses if necessary. `assigned` maps log indices to the
+/// expected access assigned to them.
+fn augment(
+    e: usize,
+    candidates: &[Vec<usize>],
+    assigned: &mut [Option<usize>],
+    visited: &mut [bool],
+) -> bool {
+    for &i in &candidates[e] {
+        if visited[i] {
+            continue;
+        }
+        visited[i] = true;
+        if assigned[i].is_none_or(|other| augment(other, candidates, assigned, visited)) {
+            assigned[i] = Some(e);
+            return true;
+        }
+    }
+    false
+}
+
+/// Verify that a sequence of [`RegisterAccess`]'s happened without other
+/// accesses in between.
+///
+/// Will succeed if the log contains the accesses of
+/// [`window`](#structfield.window) as a contiguous run, anywhere in the log.
+#[derive(Clone)]
+pub struct LogWindowMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    pub window: SEQ,
+}
+
+impl<'seq, SEQ> LogWindowMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+{
+    const NAME: &'static str = "LogWindowMatcher";
+    /// Construct new [`LogWindowMatcher`]
+    pub fn new(window: SEQ) -> Self {
+        Self { window }
+    }
+}
+
+impl<'seq, 'log, SEQ, T> LogMatcher<'log, T> for LogWindowMatcher<'seq, SEQ>
+where
+    SEQ: IntoIterator<Item = &'seq RegisterAccess>,
+    T: IntoIterator<Item = &'log RegisterAccess>,
+{
+    fn name(&self) -> &'static str {
+        Self::NAME
+    }
+
+    /// Match [`LogWindowMatcher`] against log of [`RegisterAccess`]'s.
+    fn r#match(self, log: T) -> Result<(), MatchError> {
+        let window = self.window.into_iter().collect_vec();
+        let log = log.into_iter().collect_vec();
+        // length and start of the longest match of the beginning of the window
+        let mut best = (0, 0);
+        for start in 0..log.len().max(1) {
+            let len = window
+                .iter()
+                .zip(&log[start.min(log.len())..])
+                .take_while(|(expected, actual)| expected.eq(actual))
+                .count();
+            if len == window.len() {
+                return Ok(());
+            }
+            if len > best.0 {
+                best = (len, start);
+            }
+        }
+        let (len, start) = best;
+        let stopped = match log.get(start + len) {
+            Some(actual) if len > 0 => format!(
+                "The longest match of {len} accesses starts at log index {start}, matching stopped at log index {} with\nexpected: {}\nactual:   {}",
+                start + len,
+                access_id(window[len]),
+                access_id(actual)
+            ),
+            None if len > 0 => format!(
+                "The longest match of {len} accesses starts at log index {start}, the log ended after it"
+            ),
+            _ => "The first expected access was not found".to_string(),
+        };
+        MatchError::error(
+            Self::NAME,
+            format!(
+                "Log of {} accesses does not contain the {} expected accesses contiguously. {stopped}\nExpected accesses not found:\n{}",
+                log.len(),
+                window.len(),
+                window[len..].iter().map(|e| access_id(e)).join("\n")
+            ),
+        )
+    }
+
+    /// Match the shortest beginning of the log that contains the expected accesses.
+    fn match_prefix(self, log: &[&'log RegisterAccess]) -> Result<usize, MatchError>
+    where
+        Self: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        match_shortest_prefix(self, log)
+    }
+}
+
+thread_local! {
+    /// Register names of the mock whose end of test checks are run by
+    /// [`Regmock::verify`], see [`with_names_from`].
+    static VERIFYING: RefCell<Option<Regmock>> = const { RefCell::new(None) };
+}
+
+/// Run `f` on `mock` with register names looked up in `mock` instead of the
+/// `thread_local` mock.
+///
+/// [`Regmock::verify`] runs end of test checks while the mutex of the
+/// installed mock is held, e.g. when a [`crate::MockGuard`] is dropped, so
+/// matchers used in these checks can't lock it to name registers. The
+/// [`name_resolver`](Regmock#structfield.name_resolver) of `mock` is moved
+/// to a `thread_local` mock that only names registers while `f` runs.
+pub(crate) fn with_names_from<R>(mock: &mut Regmock, f: impl FnOnce(&mut Regmock) -> R) -> R {
+    struct Restore<'m> {
+        mock: &'m mut Regmock,
+        previous: Option<Regmock>,
+    }
+    impl Drop for Restore<'_> {
+        fn drop(&mut self) {
+            let names = VERIFYING.with(|verifying| verifying.replace(self.previous.take()));
+            if let Some(mut names) = names {
+                self.mock.name_resolver = names.name_resolver.take();
+            }
+        }
+    }
+    let mut names = Regmock::default();
+    names.name_resolver = mock.name_resolver.take();
+    names.database = mock.database.clone();
+    let previous = VERIFYING.with(|verifying| verifying.replace(Some(names)));
+    let restore = Restore { mock, previous };
+    f(restore.mock)
+}
+
+/// Look up the name of the register at `addr` with the name resolver moved
+/// away from the mock by [`with_names_from`].
+pub(crate) fn resolve_verifying(addr: usize) -> Option<&'static &'static str> {
+    VERIFYING.with(|verifying| {
+        let verifying = verifying.borrow();
+        verifying.as_ref()?.name_resolver.as_ref()?(addr as u64)
+    })
+}
+
+/// Execute `f` against the mock registers are named by, see [`with_names_from`].
+fn with_names<R>(f: impl FnOnce(&Regmock) -> R) -> Result<R, MockError> {
+    VERIFYING.with(|verifying| match &*verifying.borrow() {
+        Some(names) => Ok(f(names)),
+        None => with_mock(|m| f(m)),
+    })
 }
 
 /// Get name of register or stringified address if unknown
 fn register_id(address: usize) -> String {
-    with_mock(|m| {
+    with_names(|m| {
         m.get_reg_name(address)
             .map(|n| n.to_owned())
             .unwrap_or_else(|| format!("0x{:08x}", address))
     })
     .expect("Unable to get regmock instance")
 }
+
+/// Get human-readable description of a register access, see [`Regmock::format_access`].
+fn access_id(access: &RegisterAccess) -> String {
+    with_names(|m| m.format_access(access)).unwrap_or_else(|_| format!("{:?}", access))
+}
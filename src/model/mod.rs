@@ -0,0 +1,105 @@
+//! Behavioral models of peripherals that are attached to a [`Regmock`].
+//!
+//! A [`PeripheralModel`] owns the state of a simulated peripheral and handles
+//! all accesses to the registers in its address range. Attaching it with
+//! [`Regmock::attach_model`] replaces the manual wiring of closures into
+//! `read_fn`/`write_fn` and keeps the model inspectable through the returned
+//! [`ModelHandle`].
+use std::any::Any;
+use std::marker::PhantomData;
+use std::ops::Range;
+use std::time::Duration;
+
+use crate::utils::{AccessContext, RegisterMap, Regmock};
+
+/// Behavior of a simulated peripheral.
+///
+/// All methods have a default implementation that behaves like a plain
+/// read-write register, so a model only implements what it needs.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::ops::Range;
+/// use regmock_rs::model::PeripheralModel;
+/// use regmock_rs::utils::{AccessContext, Regmock};
+///
+/// /// Counts the reads of its registers.
+/// #[derive(Default)]
+/// struct Counter {
+///     reads: u64,
+/// }
+///
+/// impl PeripheralModel for Counter {
+///     fn range(&self) -> Range<usize> {
+///         0x100..0x110
+///     }
+///
+///     fn on_read(&mut self, _ctx: &mut AccessContext) -> u64 {
+///         self.reads += 1;
+///         self.reads
+///     }
+/// }
+///
+/// let mut mock = Regmock::default();
+/// let counter = mock.attach_model(Counter::default());
+/// assert_eq!(mock.read_volatile(0x104, 4), 1);
+/// assert_eq!(mock.read_volatile(0x108, 4), 2);
+/// assert_eq!(mock.model(&counter).unwrap().reads, 2);
+/// ```
+pub trait PeripheralModel: Any + Send {
+    /// Address range of the registers handled by the model.
+    fn range(&self) -> Range<usize>;
+
+    /// Called on every read of a register in [`range`](PeripheralModel::range).
+    ///
+    /// Returns the value that is read from the register.
+    fn on_read(&mut self, ctx: &mut AccessContext) -> u64 {
+        ctx.before
+    }
+
+    /// Called on every write to a register in [`range`](PeripheralModel::range).
+    ///
+    /// Returns the value that is written to the register.
+    fn on_write(&mut self, ctx: &mut AccessContext) -> u64 {
+        ctx.value
+    }
+
+    /// Called after the registers were restored to their reset values by
+    /// [`Regmock::reset`].
+    fn on_reset(&mut self, _registers: &mut RegisterMap) {}
+
+    /// Called when the virtual time advanced by `elapsed`, see
+    /// [`Regmock::advance_time`].
+    ///
+    /// The model gets access to the whole mock, e.g. to update registers,
+    /// schedule events or raise interrupts.
+    fn on_tick(&mut self, _mock: &mut Regmock, _elapsed: Duration) {}
+}
+
+/// Handle to a [`PeripheralModel`] attached to a [`Regmock`].
+///
+/// Used to access the model after it was attached, see
+/// [`Regmock::model`].
+#[derive(Debug)]
+pub struct ModelHandle<M> {
+    pub(crate) index: usize,
+    _model: PhantomData<fn() -> M>,
+}
+
+impl<M> ModelHandle<M> {
+    pub(crate) fn new(index: usize) -> Self {
+        Self {
+            index,
+            _model: PhantomData,
+        }
+    }
+}
+
+impl<M> Clone for ModelHandle<M> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<M> Copy for ModelHandle<M> {}
@@ -0,0 +1,195 @@
+//! Replay of a recorded register trace, e.g. captured on real hardware with
+//! a debugger.
+//!
+//! A [`Replay`] set as [`Regmock::replay`] holds the ordered accesses of the
+//! trace. Reads are served with the values of the trace, writes are checked
+//! against it. The first access that diverges from the trace `panic!()`s
+//! with the accesses of the trace around it, so the regression in the driver
+//! code can be found on the host.
+use std::path::Path;
+
+use crate::logfile::{LogFile, LogFileError};
+use crate::utils::{RegisterAccess, RegisterAccessType, Regmock, RegmockLog};
+
+/// Ordered trace of register accesses replayed by a [`Regmock`].
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::replay::Replay;
+/// use regmock_rs::utils::access_gen::{read_value, write_value};
+/// use regmock_rs::utils::Regmock;
+///
+/// let mut mock = Regmock::default();
+/// mock.replay = Some(Replay::new(vec![
+///     write_value(0x100, 0x1),
+///     read_value(0x104, 0x80),
+/// ]));
+///
+/// mock.write_volatile(0x100, 4, 0x1);
+/// assert_eq!(mock.read_volatile(0x104, 4), 0x80);
+/// assert_eq!(mock.verify(), Ok(()));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Replay {
+    trace: Vec<RegisterAccess>,
+    position: usize,
+    context: usize,
+}
+
+impl Replay {
+    /// Construct a [`Replay`] of `trace`, with one entry per access.
+    pub fn new(trace: Vec<RegisterAccess>) -> Self {
+        Self {
+            trace,
+            position: 0,
+            context: 3,
+        }
+    }
+
+    /// Load the trace from a JSON file written by [`LogFile::save`].
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, LogFileError> {
+        Ok(Self::from(&LogFile::load(path)?))
+    }
+
+    /// Set the number of accesses of the trace before the divergence that are
+    /// printed when the replay diverges. Defaults to 3.
+    pub fn with_context(mut self, context: usize) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Get the number of accesses replayed so far.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Get the number of accesses of the trace that were not replayed yet.
+    pub fn remaining(&self) -> usize {
+        self.trace.len() - self.position
+    }
+
+    /// Check if all accesses of the trace were replayed.
+    pub fn is_finished(&self) -> bool {
+        self.remaining() == 0
+    }
+
+    /// Get the next access of the trace.
+    pub fn next_access(&self) -> Option<&RegisterAccess> {
+        self.trace.get(self.position)
+    }
+
+    /// Check if `access` matches the next access of the trace.
+    ///
+    /// Type, address and length have to match if they are recorded in the
+    /// trace, and the written value for writes. The written value is the
+    /// value on the bus, before write semantics like `oneToClear` were
+    /// applied. The timing and the value before the access are not compared,
+    /// neither is the value of reads, which are served from the trace.
+    fn matches(&self, access: &RegisterAccess) -> bool {
+        let Some(expected) = self.next_access() else {
+            return false;
+        };
+        let expected = RegisterAccess {
+            ty: expected.ty.clone(),
+            addr: expected.addr,
+            len: expected.len,
+            after: expected.after.filter(|_| access.is_write()),
+            mask: expected.mask,
+            ..Default::default()
+        };
+        expected == *access
+    }
+}
+
+impl From<Vec<RegisterAccess>> for Replay {
+    fn from(trace: Vec<RegisterAccess>) -> Self {
+        Self::new(trace)
+    }
+}
+
+impl From<&RegmockLog> for Replay {
+    /// Replay a recorded log, run-length-encoded **`READ`**s are replayed
+    /// once per recorded read.
+    fn from(log: &RegmockLog) -> Self {
+        Self::new(log.iter_full().cloned().collect())
+    }
+}
+
+impl From<&LogFile> for Replay {
+    fn from(file: &LogFile) -> Self {
+        Self::from(&file.to_log())
+    }
+}
+
+impl Regmock {
+    /// Check `access` against the [`replay`](#structfield.replay) trace and
+    /// advance it.
+    ///
+    /// Returns the value of the trace for **`READ`**s, `None` if no trace is
+    /// replayed.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if the access diverges from the trace or the trace ended,
+    /// the trace is not replayed further then.
+    pub(crate) fn replay_access(&mut self, access: &RegisterAccess) -> Option<u64> {
+        let replay = self.replay.as_ref()?;
+        if !self.callback_enabled {
+            return None;
+        }
+        if !replay.matches(access) {
+            let divergence = self.divergence(access);
+            // stop replaying, so the rest of the trace is not reported again
+            // when the mock is verified after the panic was caught
+            self.replay = None;
+            panic!("{divergence}");
+        }
+        let replay = self.replay.as_mut()?;
+        let expected = &replay.trace[replay.position];
+        replay.position += 1;
+        match access.ty {
+            Some(RegisterAccessType::READ) => expected.after,
+            _ => None,
+        }
+    }
+
+    /// Describe how `access` diverges from the [`replay`](#structfield.replay)
+    /// trace, with the preceding accesses of the trace as context.
+    fn divergence(&self, access: &RegisterAccess) -> String {
+        let Some(replay) = self.replay.as_ref() else {
+            return String::new();
+        };
+        let mut text = format!(
+            "Replay diverged from the trace at access {} of {}:\n",
+            replay.position,
+            replay.trace.len()
+        );
+        let start = replay.position.saturating_sub(replay.context);
+        if start < replay.position {
+            text += "after\n";
+            for expected in &replay.trace[start..replay.position] {
+                text += &format!("  {}\n", self.format_access(expected));
+            }
+        }
+        match replay.next_access() {
+            Some(expected) => text += &format!("expected\n  {}\n", self.format_access(expected)),
+            None => text += "expected the end of the trace\n",
+        }
+        text += &format!("but got\n  {}", self.format_access(access));
+        text
+    }
+
+    /// Describe the accesses of the [`replay`](#structfield.replay) trace that
+    /// were not replayed, `None` if there are none.
+    pub(crate) fn unfinished_replay(&self) -> Option<String> {
+        let replay = self.replay.as_ref()?;
+        let next = replay.next_access()?;
+        Some(format!(
+            "replay ended after {} of {} accesses of the trace, next expected {}",
+            replay.position,
+            replay.trace.len(),
+            self.format_access(next)
+        ))
+    }
+}
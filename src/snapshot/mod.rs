@@ -0,0 +1,166 @@
+//! Snapshot testing of access logs.
+//!
+//! [`assert_log_snapshot!`](crate::assert_log_snapshot) renders the log of
+//! the mock, see [`Regmock::format_snapshot`], and compares it with the
+//! snapshot stored in `snapshots/<name>.snap` of the package under test. On
+//! a difference the test fails with a line diff and the new rendering is
+//! written next to the snapshot as `<name>.snap.new` to be reviewed.
+//!
+//! Set the environment variable [`UPDATE_ENV`] to `1` to accept new and
+//! changed snapshots instead, e.g. `REGMOCK_UPDATE_SNAPSHOTS=1 cargo test`.
+use std::path::Path;
+
+use crate::diff::{edit_script, Edit};
+use crate::utils::Regmock;
+
+/// Environment variable that accepts new and changed snapshots if set to `1`.
+pub const UPDATE_ENV: &str = "REGMOCK_UPDATE_SNAPSHOTS";
+
+/// Number of unchanged lines shown around a changed line of a snapshot.
+const CONTEXT: usize = 2;
+
+impl Regmock {
+    /// Render the [`log`](#structfield.log) for a snapshot, one line per log
+    /// entry like [`Regmock::format_log`] but without source locations, so
+    /// the rendering does not change when unrelated code moves.
+    pub fn format_snapshot(&self) -> String {
+        let mut snapshot = String::new();
+        for (access, count) in &self.log.log {
+            let mut access = access.clone();
+            access.location = None;
+            snapshot += &self.format_access(&access);
+            if *count > 1 {
+                snapshot += &format!(" (x{count})");
+            }
+            snapshot.push('\n');
+        }
+        snapshot
+    }
+}
+
+/// Compare `snapshot` with the one stored as `<name>.snap` in `dir`.
+///
+/// Used by [`assert_log_snapshot!`](crate::assert_log_snapshot), new and
+/// changed snapshots are accepted if [`UPDATE_ENV`] is set to `1`.
+///
+/// # Panics
+///
+/// Will panic if [`check_snapshot`] fails.
+#[track_caller]
+pub fn assert_snapshot(dir: impl AsRef<Path>, name: &str, snapshot: &str) {
+    let update = std::env::var(UPDATE_ENV).is_ok_and(|value| value == "1");
+    if let Err(message) = check_snapshot(dir, name, snapshot, update) {
+        panic!("{message}");
+    }
+}
+
+/// Compare `snapshot` with the one stored as `<name>.snap` in `dir`.
+///
+/// If `update` is set, a new or changed snapshot is stored. Otherwise it is
+/// written to `<name>.snap.new` for review and an error describing the
+/// difference is returned. A pending `<name>.snap.new` is removed once the
+/// snapshot matches.
+pub fn check_snapshot(
+    dir: impl AsRef<Path>,
+    name: &str,
+    snapshot: &str,
+    update: bool,
+) -> Result<(), String> {
+    let path = dir.as_ref().join(format!("{name}.snap"));
+    let new_path = dir.as_ref().join(format!("{name}.snap.new"));
+    let stored = std::fs::read_to_string(&path).ok();
+    if stored.as_deref() == Some(snapshot) {
+        let _ = std::fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    let target = if update { &path } else { &new_path };
+    std::fs::create_dir_all(dir.as_ref())
+        .and_then(|_| std::fs::write(target, snapshot))
+        .map_err(|e| format!("Could not write snapshot {}: {e}", target.display()))?;
+    if update {
+        let _ = std::fs::remove_file(&new_path);
+        return Ok(());
+    }
+
+    Err(match stored {
+        Some(stored) => format!(
+            "Snapshot '{name}' does not match {}:\n{}\n\
+             The new snapshot was written to {}, set {UPDATE_ENV}=1 to accept it.",
+            path.display(),
+            line_diff(&stored, snapshot),
+            new_path.display()
+        ),
+        None => format!(
+            "Snapshot '{name}' does not exist yet. The new snapshot was written \
+             to {}, set {UPDATE_ENV}=1 to accept it:\n{snapshot}",
+            new_path.display()
+        ),
+    })
+}
+
+/// Render the differences between two snapshots, one line per line of the
+/// snapshots starting with `-` for removed and `+` for inserted lines.
+fn line_diff(old: &str, new: &str) -> String {
+    let old: Vec<_> = old.lines().collect();
+    let new: Vec<_> = new.lines().collect();
+    let edits = edit_script(&old, &new, |o, n| o == n);
+    let is_change = |edit: &Edit| !matches!(edit, Edit::Both(..));
+
+    let mut lines = Vec::new();
+    let mut skipped = 0;
+    for (index, edit) in edits.iter().enumerate() {
+        let start = index.saturating_sub(CONTEXT);
+        let end = (index + CONTEXT + 1).min(edits.len());
+        if !edits[start..end].iter().any(is_change) {
+            skipped += 1;
+            continue;
+        }
+        if skipped > 0 {
+            lines.push(format!("  ... {skipped} unchanged"));
+            skipped = 0;
+        }
+        lines.push(match *edit {
+            Edit::Both(i, _) => format!("  {}", old[i]),
+            Edit::Old(i) => format!("- {}", old[i]),
+            Edit::New(j) => format!("+ {}", new[j]),
+        });
+    }
+    if skipped > 0 {
+        lines.push(format!("  ... {skipped} unchanged"));
+    }
+    lines.join("\n")
+}
+
+/// Compare the log of the `thread_local` mock, or of the given [`Regmock`],
+/// with the snapshot `snapshots/<name>.snap` of the package under test.
+///
+/// See the [`snapshot`](crate::snapshot) module for how snapshots are
+/// stored and accepted.
+///
+/// # Example
+///
+/// ```rust,ignore
+/// unsafe { pac::SPI.ctrl().write(pac::spi::Ctrl::new(0x1)) };
+/// assert_log_snapshot!("spi_init");
+/// ```
+#[macro_export]
+macro_rules! assert_log_snapshot {
+    ($name: expr) => {{
+        let snapshot = regmock_rs::with_mock(|mock| mock.format_snapshot())
+            .expect("Couldn't get regmock thread-local for the snapshot. Most likely you forgot to initialize regmock.");
+        regmock_rs::snapshot::assert_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"),
+            $name,
+            &snapshot,
+        );
+    }};
+    ($name: expr, $mock: expr) => {{
+        let snapshot = $mock.format_snapshot();
+        regmock_rs::snapshot::assert_snapshot(
+            concat!(env!("CARGO_MANIFEST_DIR"), "/snapshots"),
+            $name,
+            &snapshot,
+        );
+    }};
+}
@@ -0,0 +1,311 @@
+//! Minimal loader for CMSIS-SVD files.
+//!
+//! Only the parts of the SVD format that are needed to mock registers are
+//! evaluated: peripherals (including `derivedFrom` peripherals), clusters and
+//! registers (including `dim` arrays) together with their addresses, sizes,
+//! access rights, reset values and bitfields.
+//!
+//! Register arrays and registers in cluster arrays are expanded, so there is
+//! exactly one [`RegisterInfo`] per physical register address.
+//!
+//! Register names are built the same way `svd2pac` names the register
+//! accessors, e.g. `TIMER.timercluster()[1].ctrlstat()`.
+use std::path::Path;
+
+use roxmltree::{Document, Node};
+
+use crate::database::{Access, FieldInfo, ReadAction, RegisterInfo, WriteAction};
+
+/// Errors generated when loading an SVD file.
+#[derive(Debug, Clone)]
+pub enum SvdError {
+    /// The SVD file could not be read.
+    Io(String),
+    /// The SVD file is not well-formed XML.
+    Xml(String),
+    /// The SVD file is missing required elements or contains invalid values.
+    Invalid(String),
+}
+
+impl From<SvdError> for String {
+    fn from(value: SvdError) -> Self {
+        format!("failed to load SVD due to: {:?}", value)
+    }
+}
+
+/// Load all registers from the SVD file at `path`.
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<RegisterInfo>, SvdError> {
+    let path = path.as_ref();
+    let svd = std::fs::read_to_string(path)
+        .map_err(|e| SvdError::Io(format!("{}: {}", path.display(), e)))?;
+    parse(&svd)
+}
+
+/// Parse all registers from the content of an SVD file.
+pub fn parse(svd: &str) -> Result<Vec<RegisterInfo>, SvdError> {
+    let doc = Document::parse(svd).map_err(|e| SvdError::Xml(e.to_string()))?;
+    let device = doc.root_element();
+    if !device.has_tag_name("device") {
+        return Err(SvdError::Invalid(format!(
+            "expected <device> as root element, found <{}>",
+            device.tag_name().name()
+        )));
+    }
+    let properties = Properties::default().inherit(device)?;
+    let peripherals: Vec<Node> = child(device, "peripherals")
+        .ok_or_else(|| SvdError::Invalid("missing <peripherals> element".to_owned()))?
+        .children()
+        .filter(|n| n.has_tag_name("peripheral"))
+        .collect();
+
+    let mut registers = Vec::new();
+    for peripheral in &peripherals {
+        let source = match peripheral.attribute("derivedFrom") {
+            Some(base) => peripherals
+                .iter()
+                .find(|p| child_text(**p, "name") == Some(base))
+                .ok_or_else(|| {
+                    SvdError::Invalid(format!("derivedFrom unknown peripheral '{base}'"))
+                })?,
+            None => peripheral,
+        };
+        let name = required_text(*peripheral, "name")?.to_uppercase();
+        let base_address = parse_number(required_text(*peripheral, "baseAddress")?)? as usize;
+        let properties = properties.inherit(*source)?.inherit(*peripheral)?;
+        if let Some(node) = child(*peripheral, "registers").or_else(|| child(*source, "registers"))
+        {
+            let context = Context {
+                peripheral: &name,
+                base_address,
+                path: &name,
+            };
+            collect_registers(node, &context, &properties, &mut registers)?;
+        }
+    }
+    Ok(registers)
+}
+
+/// Register properties that are inherited from the device down to the registers.
+#[derive(Debug, Clone, Default)]
+struct Properties {
+    size: Option<u64>,
+    access: Option<Access>,
+    reset_value: Option<u64>,
+    reset_mask: Option<u64>,
+}
+
+impl Properties {
+    /// Override the inherited properties by the ones specified on `node`.
+    fn inherit(&self, node: Node) -> Result<Self, SvdError> {
+        let number = |tag| child_text(node, tag).map(parse_number).transpose();
+        Ok(Self {
+            size: number("size")?.or(self.size),
+            access: parse_access(node)?.or(self.access),
+            reset_value: number("resetValue")?.or(self.reset_value),
+            reset_mask: number("resetMask")?.or(self.reset_mask),
+        })
+    }
+}
+
+/// Location of a `<registers>` or `<cluster>` node in the device.
+struct Context<'a> {
+    peripheral: &'a str,
+    base_address: usize,
+    path: &'a str,
+}
+
+/// Walk the children of a `<registers>` or `<cluster>` node and collect all registers.
+fn collect_registers(
+    node: Node,
+    context: &Context,
+    properties: &Properties,
+    registers: &mut Vec<RegisterInfo>,
+) -> Result<(), SvdError> {
+    for element in node.children().filter(|n| n.is_element()) {
+        let is_register = match element.tag_name().name() {
+            "register" => true,
+            "cluster" => false,
+            _ => continue,
+        };
+        let offset = parse_number(required_text(element, "addressOffset")?)? as usize;
+        let properties = properties.inherit(element)?;
+        for (increment, segment) in expand_dim(element)? {
+            let address = context.base_address + offset + increment;
+            let path = format!("{}.{segment}", context.path);
+            if is_register {
+                let size = properties.size.unwrap_or(32) as usize;
+                let access = properties.access.unwrap_or_default();
+                let reset_mask = properties.reset_mask.unwrap_or(u64::MAX) & width_mask(size);
+                registers.push(RegisterInfo {
+                    name: path,
+                    peripheral: context.peripheral.to_owned(),
+                    address,
+                    size,
+                    access,
+                    reset_value: properties.reset_value.unwrap_or(0) & reset_mask,
+                    reset_mask,
+                    fields: parse_fields(element, access)?,
+                });
+            } else {
+                let context = Context {
+                    peripheral: context.peripheral,
+                    base_address: address,
+                    path: &path,
+                };
+                collect_registers(element, &context, &properties, registers)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse the `<fields>` of a register, ordered by their bit offset.
+fn parse_fields(register: Node, register_access: Access) -> Result<Vec<FieldInfo>, SvdError> {
+    let Some(fields) = child(register, "fields") else {
+        return Ok(Vec::new());
+    };
+    let mut fields = fields
+        .children()
+        .filter(|n| n.has_tag_name("field"))
+        .map(|field| {
+            let (bit_offset, bit_width) = parse_bit_range(field)?;
+            Ok(FieldInfo {
+                name: required_text(field, "name")?.to_owned(),
+                bit_offset,
+                bit_width,
+                access: parse_access(field)?.unwrap_or(register_access),
+                write_action: parse_enum(field, "modifiedWriteValues", WriteAction::from_svd)?,
+                read_action: parse_enum(field, "readAction", ReadAction::from_svd)?,
+            })
+        })
+        .collect::<Result<Vec<_>, SvdError>>()?;
+    fields.sort_by_key(|f| f.bit_offset);
+    Ok(fields)
+}
+
+/// Parse the position of a field given in any of the three SVD styles
+/// (`bitOffset`/`bitWidth`, `lsb`/`msb` or `bitRange`).
+fn parse_bit_range(field: Node) -> Result<(usize, usize), SvdError> {
+    let number = |tag| -> Result<Option<usize>, SvdError> {
+        Ok(child_text(field, tag)
+            .map(parse_number)
+            .transpose()?
+            .map(|n| n as usize))
+    };
+    if let Some(offset) = number("bitOffset")? {
+        return Ok((offset, number("bitWidth")?.unwrap_or(1)));
+    }
+    if let (Some(lsb), Some(msb)) = (number("lsb")?, number("msb")?) {
+        let width = (msb + 1).checked_sub(lsb).ok_or_else(|| {
+            SvdError::Invalid(format!("invalid field position lsb {lsb} msb {msb}"))
+        })?;
+        return Ok((lsb, width));
+    }
+    if let Some(range) = child_text(field, "bitRange") {
+        let invalid = || SvdError::Invalid(format!("invalid bitRange '{range}'"));
+        let (msb, lsb) = range
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split_once(':')
+            .ok_or_else(invalid)?;
+        let msb = parse_number(msb)? as usize;
+        let lsb = parse_number(lsb)? as usize;
+        return Ok((lsb, (msb + 1).checked_sub(lsb).ok_or_else(invalid)?));
+    }
+    Err(SvdError::Invalid(format!(
+        "field '{}' has no bit position",
+        child_text(field, "name").unwrap_or_default()
+    )))
+}
+
+fn parse_enum<T>(
+    node: Node,
+    tag: &str,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Result<Option<T>, SvdError> {
+    child_text(node, tag)
+        .map(|v| parse(v).ok_or_else(|| SvdError::Invalid(format!("invalid {tag} '{v}'"))))
+        .transpose()
+}
+
+fn parse_access(node: Node) -> Result<Option<Access>, SvdError> {
+    child_text(node, "access")
+        .map(|a| {
+            Access::from_svd(a).ok_or_else(|| SvdError::Invalid(format!("invalid access '{a}'")))
+        })
+        .transpose()
+}
+
+/// Expand a (possibly `dim`ensioned) register or cluster into a list of
+/// address increments and the PAC accessor names of the instances.
+fn expand_dim(node: Node) -> Result<Vec<(usize, String)>, SvdError> {
+    let name = required_text(node, "name")?;
+    let base = accessor(&name.replace("[%s]", "").replace("%s", ""));
+    match child_text(node, "dim") {
+        None => Ok(vec![(0, base)]),
+        Some(dim) => {
+            let dim = parse_number(dim)? as usize;
+            let increment = parse_number(required_text(node, "dimIncrement")?)? as usize;
+            Ok((0..dim)
+                .map(|i| (i * increment, format!("{base}[{i}]")))
+                .collect())
+        }
+    }
+}
+
+/// Build the name of the PAC accessor function for an SVD element name.
+fn accessor(name: &str) -> String {
+    const KEYWORDS: &[&str] = &[
+        "as", "break", "const", "continue", "crate", "else", "enum", "extern", "false", "fn",
+        "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub", "ref",
+        "return", "static", "struct", "super", "trait", "true", "type", "unsafe", "use", "where",
+        "while", "async", "await", "dyn",
+    ];
+    let name = name.to_lowercase();
+    if KEYWORDS.contains(&name.as_str()) {
+        format!("r#{name}()")
+    } else {
+        format!("{name}()")
+    }
+}
+
+/// Mask with the lower `bits` bits set.
+pub(crate) fn width_mask(bits: usize) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1u64 << bits) - 1
+    }
+}
+
+/// Parse an SVD `scaledNonNegativeInteger`.
+fn parse_number(text: &str) -> Result<u64, SvdError> {
+    let text = text.trim();
+    let (digits, radix) =
+        if let Some(hex) = text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+            (hex, 16)
+        } else if let Some(bin) = text.strip_prefix('#') {
+            (bin, 2)
+        } else {
+            (text, 10)
+        };
+    u64::from_str_radix(digits, radix)
+        .map_err(|e| SvdError::Invalid(format!("invalid number '{text}': {e}")))
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|n| n.has_tag_name(tag))
+}
+
+fn child_text<'a>(node: Node<'a, '_>, tag: &str) -> Option<&'a str> {
+    child(node, tag).and_then(|n| n.text()).map(str::trim)
+}
+
+fn required_text<'a>(node: Node<'a, '_>, tag: &str) -> Result<&'a str, SvdError> {
+    child_text(node, tag).ok_or_else(|| {
+        SvdError::Invalid(format!(
+            "<{}> is missing required element <{tag}>",
+            node.tag_name().name()
+        ))
+    })
+}
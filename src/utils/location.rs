@@ -0,0 +1,219 @@
+//! Source code locations of logged register accesses.
+use std::fmt::{Debug, Display};
+use std::panic::Location;
+use std::sync::{Arc, OnceLock};
+
+#[cfg(feature = "backtrace")]
+use backtrace::Backtrace;
+
+/// File, line and column in the source code.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct SourceLocation {
+    /// Path of the source file.
+    pub file: String,
+    /// Line in the file, starting at 1.
+    pub line: u32,
+    /// Column in the line, starting at 1.
+    pub column: u32,
+}
+
+impl Display for SourceLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}:{}", self.file, self.line, self.column)
+    }
+}
+
+impl From<&Location<'_>> for SourceLocation {
+    fn from(location: &Location<'_>) -> Self {
+        Self {
+            file: location.file().to_owned(),
+            line: location.line(),
+            column: location.column(),
+        }
+    }
+}
+
+/// Location of the code that performed a register access, see
+/// [`Regmock::capture_locations`](super::Regmock#structfield.capture_locations).
+///
+/// Accesses through the functions of [`Regmock`](super::Regmock) and the
+/// access functions of this crate are located with `#[track_caller]`. The PAC
+/// calls the access functions through a function pointer, which hides the
+/// caller, so with the `backtrace` feature the backtrace of these accesses is
+/// captured instead and only resolved to a [`SourceLocation`] when it is
+/// needed, e.g. to print the access. Without the feature they are not located.
+#[derive(Clone)]
+pub struct AccessLocation {
+    inner: Arc<Inner>,
+}
+
+struct Inner {
+    origin: Origin,
+    resolved: OnceLock<Option<SourceLocation>>,
+}
+
+enum Origin {
+    Caller(&'static Location<'static>),
+    #[cfg(feature = "backtrace")]
+    Backtrace {
+        backtrace: Backtrace,
+        filter: Vec<String>,
+    },
+}
+
+impl Origin {
+    /// Capture the unresolved backtrace of the current access.
+    #[cfg(feature = "backtrace")]
+    fn backtrace(filter: &[String]) -> Option<Self> {
+        Some(Origin::Backtrace {
+            backtrace: Backtrace::new_unresolved(),
+            filter: filter.to_vec(),
+        })
+    }
+
+    #[cfg(not(feature = "backtrace"))]
+    fn backtrace(_filter: &[String]) -> Option<Self> {
+        None
+    }
+}
+
+/// Crates whose frames never are the location of an access.
+#[cfg(feature = "backtrace")]
+const SKIPPED_CRATES: [&str; 5] = ["std::", "core::", "alloc::", "backtrace::", "regmock_rs::"];
+
+impl AccessLocation {
+    /// Locate an access performed by `caller`.
+    ///
+    /// If `caller` is the definition of an access function of this crate,
+    /// which is what `#[track_caller]` reports for calls through a function
+    /// pointer, the backtrace of the access is captured instead. Frames whose
+    /// symbol contains one of the strings in `filter` are skipped when
+    /// resolving it, in addition to those of `std` and `regmock_rs`.
+    ///
+    /// Returns `None` for these accesses without the `backtrace` feature.
+    pub(crate) fn capture(caller: &'static Location<'static>, filter: &[String]) -> Option<Self> {
+        let origin = if caller.file() == crate::ACCESS_FN_FILE {
+            Origin::backtrace(filter)?
+        } else {
+            Origin::Caller(caller)
+        };
+        Some(Self {
+            inner: Arc::new(Inner {
+                origin,
+                resolved: OnceLock::new(),
+            }),
+        })
+    }
+
+    /// Get the caller of the access if it was located with `#[track_caller]`.
+    pub fn caller(&self) -> Option<&'static Location<'static>> {
+        match self.inner.origin {
+            Origin::Caller(caller) => Some(caller),
+            #[cfg(feature = "backtrace")]
+            Origin::Backtrace { .. } => None,
+        }
+    }
+
+    /// Get the unresolved backtrace of the access if the caller was unknown.
+    #[cfg(feature = "backtrace")]
+    pub fn backtrace(&self) -> Option<&Backtrace> {
+        match &self.inner.origin {
+            Origin::Caller(_) => None,
+            Origin::Backtrace { backtrace, .. } => Some(backtrace),
+        }
+    }
+
+    /// Get the location of the caller, or of the innermost frame of the
+    /// backtrace outside of `regmock_rs`, the standard library and the
+    /// filtered PAC frames.
+    ///
+    /// Returns `None` if the backtrace has no debug information.
+    pub fn resolve(&self) -> Option<&SourceLocation> {
+        self.inner
+            .resolved
+            .get_or_init(|| match &self.inner.origin {
+                Origin::Caller(caller) => Some(SourceLocation::from(*caller)),
+                #[cfg(feature = "backtrace")]
+                Origin::Backtrace { backtrace, filter } => {
+                    let mut backtrace = backtrace.clone();
+                    backtrace.resolve();
+                    locate(&backtrace, filter)
+                }
+            })
+            .as_ref()
+    }
+}
+
+/// Find the innermost symbol of `backtrace` that is not skipped.
+#[cfg(feature = "backtrace")]
+fn locate(backtrace: &Backtrace, filter: &[String]) -> Option<SourceLocation> {
+    let cwd = std::env::current_dir().ok();
+    backtrace
+        .frames()
+        .iter()
+        .flat_map(|frame| frame.symbols())
+        .find_map(|symbol| {
+            let name = symbol.name()?.to_string();
+            let path = name.trim_start_matches(['<', '&']);
+            if SKIPPED_CRATES.iter().any(|c| path.starts_with(c))
+                || filter.iter().any(|f| name.contains(f.as_str()))
+            {
+                return None;
+            }
+            let file = symbol.filename()?;
+            // the standard library is built with paths below `/rustc/<hash>`
+            if file.starts_with("/rustc") {
+                return None;
+            }
+            let file = cwd
+                .as_deref()
+                .and_then(|cwd| file.strip_prefix(cwd).ok())
+                .unwrap_or(file);
+            Some(SourceLocation {
+                file: file.to_string_lossy().into_owned(),
+                line: symbol.lineno()?,
+                column: symbol.colno().unwrap_or(1),
+            })
+        })
+}
+
+impl Display for AccessLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.resolve() {
+            Some(location) => write!(f, "{location}"),
+            None => f.write_str("<unknown>"),
+        }
+    }
+}
+
+impl Debug for AccessLocation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self}")
+    }
+}
+
+/// Locations are equal if they resolve to the same [`SourceLocation`].
+///
+/// Backtraces of the same frames are equal without resolving them, so
+/// comparing the accesses of a polling loop stays cheap.
+impl PartialEq for AccessLocation {
+    fn eq(&self, other: &Self) -> bool {
+        #[cfg(feature = "backtrace")]
+        if let (
+            Origin::Backtrace { backtrace, .. },
+            Origin::Backtrace {
+                backtrace: other_backtrace,
+                ..
+            },
+        ) = (&self.inner.origin, &other.inner.origin)
+        {
+            let ips = |b: &Backtrace| b.frames().iter().map(|f| f.ip()).collect::<Vec<_>>();
+            if ips(backtrace) == ips(other_backtrace) {
+                return true;
+            }
+        }
+        self.resolve() == other.resolve()
+    }
+}
+
+impl Eq for AccessLocation {}
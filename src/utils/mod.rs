@@ -1,15 +1,33 @@
 //! Collection of data structures and functions that power `regmock_rs`.
+use std::any::Any;
 use std::cell::RefCell;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt::Debug;
+use std::ops::Range;
+use std::panic::Location;
+use std::path::Path;
 use std::rc::Rc;
+use std::sync::Arc;
+use std::thread::ThreadId;
+use std::time::{Duration, SystemTime};
 
 use derive_builder::Builder;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json;
 
+use crate::database::{Access, MemoryMap, ReadAction, RegisterDatabase, RegisterInfo, WriteAction};
+use crate::expectation::{Expectation, ExpectedAccess};
+use crate::interrupt::{InterruptController, InterruptEvent, InterruptEventKind, Isr};
+use crate::logfile::LogFileError;
+use crate::model::{ModelHandle, PeripheralModel};
+use crate::replay::Replay;
+use crate::svd::SvdError;
+
+mod location;
+pub use location::{AccessLocation, SourceLocation};
+
 /// Enum representing types of register accesses.
-#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
 pub enum RegisterAccessType {
     #[serde(alias = "r")]
@@ -18,6 +36,12 @@ pub enum RegisterAccessType {
     WRITE,
     #[cfg(feature = "aurix")]
     LDMST,
+    #[cfg(feature = "aurix")]
+    SWAP,
+    #[cfg(feature = "aurix")]
+    SWAPMSK,
+    #[cfg(feature = "aurix")]
+    CMPSWAP,
 }
 
 /// Stores information of a specific registers access.
@@ -29,26 +53,68 @@ pub enum RegisterAccessType {
 /// See the convenience functions [`access_gen::read`], [`access_gen::read_value`],
 /// [`access_gen::write`] and [`access_gen::write_value`] for a shorthand ways to
 /// construct `RegisterAccess` structs.
-#[derive(Default, Clone, Eq, Builder, Deserialize)]
+#[derive(Default, Clone, Eq, Builder, Serialize, Deserialize)]
 #[builder(default)]
 #[serde(default)]
 pub struct RegisterAccess {
     /// Type of the register access.
-    #[serde(alias = "type")]
+    #[serde(alias = "type", skip_serializing_if = "Option::is_none")]
     #[builder(setter(into, strip_option))]
     pub ty: Option<RegisterAccessType>,
     /// Address of accessed register.
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(setter(into, strip_option))]
     pub addr: Option<usize>,
     /// Length of the access mask in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(setter(into, strip_option))]
     pub len: Option<usize>,
     /// Value of the register before the access.
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(setter(into, strip_option))]
     pub before: Option<u64>,
     /// Value of the register after the access.
+    #[serde(skip_serializing_if = "Option::is_none")]
     #[builder(setter(into, strip_option))]
     pub after: Option<u64>,
+    /// Virtual time of the access, see [`Regmock::now`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub time: Option<Duration>,
+    /// Bits modified by a masked access (e.g. `LDMST`). `None` for accesses
+    /// that modify all bits.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub mask: Option<u64>,
+    /// Position of the access in the log of its [`Regmock`]. Counts every
+    /// logged access, so a run of merged reads covers `count` numbers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub seq: Option<u64>,
+    /// Wall-clock time of the access.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub wall_time: Option<SystemTime>,
+    /// Virtual time of the last access of a run of merged **`READ`**s, equal
+    /// to [`time`](#structfield.time) for single accesses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub last_time: Option<Duration>,
+    /// Wall-clock time of the last access of a run of merged **`READ`**s,
+    /// equal to [`wall_time`](#structfield.wall_time) for single accesses.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[builder(setter(into, strip_option))]
+    pub last_wall_time: Option<SystemTime>,
+    /// Thread that performed the access.
+    #[serde(skip)]
+    #[builder(setter(into, strip_option))]
+    pub thread: Option<ThreadId>,
+    /// Location of the code that performed the access, recorded if
+    /// [`Regmock::capture_locations`](Regmock#structfield.capture_locations)
+    /// is enabled. Not compared by [`PartialEq`].
+    #[serde(skip)]
+    #[builder(setter(into, strip_option))]
+    pub location: Option<AccessLocation>,
 }
 
 impl Debug for RegisterAccess {
@@ -69,6 +135,30 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         if let Some(after) = &self.after {
             debug_struct.field("after", after);
         }
+        if let Some(time) = &self.time {
+            debug_struct.field("time", time);
+        }
+        if let Some(mask) = &self.mask {
+            debug_struct.field("mask", mask);
+        }
+        if let Some(seq) = &self.seq {
+            debug_struct.field("seq", seq);
+        }
+        if let Some(wall_time) = &self.wall_time {
+            debug_struct.field("wall_time", wall_time);
+        }
+        if let Some(last_time) = &self.last_time {
+            debug_struct.field("last_time", last_time);
+        }
+        if let Some(last_wall_time) = &self.last_wall_time {
+            debug_struct.field("last_wall_time", last_wall_time);
+        }
+        if let Some(thread) = &self.thread {
+            debug_struct.field("thread", thread);
+        }
+        if let Some(location) = &self.location {
+            debug_struct.field("location", location);
+        }
         debug_struct.finish()
     }
 }
@@ -79,6 +169,11 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 /// Consequent this means that a `RegisterAccess` struct with all members
 /// `None` is equal to every `RegisterAccess` struct.
 ///
+/// Only the access itself is compared (`ty`, `addr`, `len`, `before`, `after`
+/// and `mask`), so the accesses of two identical runs are equal. The metadata
+/// recorded by [`Regmock`] (sequence number, times, thread and location) is
+/// not compared, see e.g. [`RegmockLog::iter_thread`] to filter by thread.
+///
 /// # Examples
 ///
 /// ```rust
@@ -112,6 +207,9 @@ fn eq(&self, other: &Self) -> bool {
         if self.after.is_some() && other.after.is_some() {
             ret = ret && self.after.eq(&other.after);
         }
+        if self.mask.is_some() && other.mask.is_some() {
+            ret = ret && self.mask.eq(&other.mask);
+        }
         ret
     }
 }
@@ -129,11 +227,30 @@ pub fn new(ty: RegisterAccessType, addr: usize, len: usize, before: u64, after:
             len: Some(len),
             before: Some(before),
             after: Some(after),
+            ..Default::default()
         }
     }
+    /// Check if the access is a **`WRITE`** or another access that writes the
+    /// register (e.g. `LDMST` or `SWAP`).
+    ///
+    /// A `CMPSWAP` only counts if it changed the value, a failed comparison
+    /// does not write the register.
+    pub fn is_write(&self) -> bool {
+        match self.ty {
+            Some(RegisterAccessType::WRITE) => true,
+            #[cfg(feature = "aurix")]
+            Some(
+                RegisterAccessType::LDMST | RegisterAccessType::SWAP | RegisterAccessType::SWAPMSK,
+            ) => true,
+            #[cfg(feature = "aurix")]
+            Some(RegisterAccessType::CMPSWAP) => self.before != self.after,
+            _ => false,
+        }
+    }
+
     /// Deserialize a sequence of register accesses from a JSON array.
-    pub fn seq_from_json(data: &str) -> Vec<RegisterAccess> {
-        serde_json::from_str(data).unwrap()
+    pub fn seq_from_json(data: &str) -> Result<Vec<RegisterAccess>, LogFileError> {
+        Ok(serde_json::from_str(data)?)
     }
 }
 
@@ -149,9 +266,7 @@ pub fn read(address: usize) -> RegisterAccess {
         RegisterAccess {
             ty: Some(READ),
             addr: Some(address),
-            len: None,
-            before: None,
-            after: None,
+            ..Default::default()
         }
     }
 
@@ -161,9 +276,8 @@ pub fn read_value(address: usize, value: u64) -> RegisterAccess {
         RegisterAccess {
             ty: Some(READ),
             addr: Some(address),
-            len: None,
-            before: None,
             after: Some(value),
+            ..Default::default()
         }
     }
 
@@ -173,9 +287,7 @@ pub fn write(address: usize) -> RegisterAccess {
         RegisterAccess {
             ty: Some(WRITE),
             addr: Some(address),
-            len: None,
-            before: None,
-            after: None,
+            ..Default::default()
         }
     }
 
@@ -185,37 +297,184 @@ pub fn write_value(address: usize, value: u64) -> RegisterAccess {
         RegisterAccess {
             ty: Some(WRITE),
             addr: Some(address),
-            len: None,
-            before: None,
             after: Some(value),
+            ..Default::default()
+        }
+    }
+}
+
+/// Controls how [`Regmock`] reacts to register accesses that violate the
+/// access rights of a register or that are outside of the known memory map.
+///
+/// Access rights are taken from the [`Regmock::database`]. Registers without
+/// metadata are never checked. Accesses are only checked against the
+/// [`Regmock::memory_map`] if one is set. Accesses with callbacks disabled (e.g. test-side
+/// accesses through [`crate::silent`]) are not checked either.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AccessPolicy {
+    /// Access rights are not checked.
+    #[default]
+    Permissive,
+    /// `panic!()` on the first violating access.
+    Panic,
+    /// Record an [`AccessViolation`] in [`RegmockLog::violations`] and
+    /// perform the access anyway.
+    Record,
+    /// Record an [`AccessViolation`] and behave like hardware would:
+    /// violating writes are dropped and violating reads return `0`.
+    Drop,
+}
+
+/// Kind of an [`AccessViolation`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ViolationKind {
+    /// Write to a **read-only** register.
+    WriteToReadOnly,
+    /// Read from a **write-only** register.
+    ReadFromWriteOnly,
+    /// Repeated write to a **write-once** register since the last reset.
+    RepeatedWriteOnce,
+    /// Access to an address that is not a multiple of the access width.
+    Misaligned,
+    /// Access that extends beyond the end of the accessed register.
+    Oversized {
+        /// Size of the accessed register in bytes.
+        register_size: usize,
+    },
+    /// Access outside of the [`Regmock::memory_map`].
+    Unmapped {
+        /// Name of the known register or memory region closest to the address.
+        nearest: Option<String>,
+    },
+}
+
+/// Register access that violated the rules enforced by [`Regmock`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AccessViolation {
+    /// Kind of the violation.
+    pub kind: ViolationKind,
+    /// The violating access. For writes, `after` holds the value that should
+    /// have been written.
+    pub access: RegisterAccess,
+    /// Number of entries in [`RegmockLog::log`] before the violating access,
+    /// i.e. where in the log the violation happened.
+    pub log_index: usize,
+}
+
+/// Hardware semantics of the bits of a register that [`Regmock`] applies on
+/// every access, e.g. status bits that are cleared by writing `1` to them.
+///
+/// See [`Regmock::field_semantics`].
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::database::WriteAction;
+/// use regmock_rs::utils::{FieldSemantics, Regmock};
+///
+/// let mut mock = Regmock::default();
+/// mock.field_semantics
+///     .insert(0x1000, vec![FieldSemantics::on_write(0xF, WriteAction::OneToClear)]);
+/// mock.register_mocks.insert(0x1000, 0xFF);
+/// mock.write_volatile(0x1000, 4, 0x03);
+/// assert_eq!(mock.register_mocks[&0x1000], 0x0C);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSemantics {
+    /// Bits of the register the semantics apply to.
+    pub mask: u64,
+    /// Effect of writes to the bits. `None` stores the written value.
+    pub write: Option<WriteAction>,
+    /// Side effect of reads from the bits.
+    pub read: Option<ReadAction>,
+}
+
+impl FieldSemantics {
+    /// Semantics of bits with a special effect on writes.
+    pub fn on_write(mask: u64, action: WriteAction) -> Self {
+        Self {
+            mask,
+            write: Some(action),
+            read: None,
+        }
+    }
+
+    /// Semantics of bits with a side effect on reads.
+    pub fn on_read(mask: u64, action: ReadAction) -> Self {
+        Self {
+            mask,
+            write: None,
+            read: Some(action),
         }
     }
+
+    /// Derive the semantics of the bitfields of a register from its metadata.
+    ///
+    /// Uses the `modifiedWriteValues` and `readAction` of the bitfields.
+    /// Writes to **read-only** bitfields and to reserved bits (bits not
+    /// covered by any bitfield) are ignored.
+    pub fn from_register(register: &RegisterInfo) -> Vec<Self> {
+        if register.fields.is_empty() {
+            return Vec::new();
+        }
+        let mut semantics: Vec<Self> = register
+            .fields
+            .iter()
+            .map(|field| Self {
+                mask: field.mask(),
+                write: match (field.write_action, field.access) {
+                    (Some(action), _) => Some(action),
+                    (None, Access::ReadOnly) => Some(WriteAction::Ignore),
+                    (None, _) => None,
+                },
+                read: field.read_action,
+            })
+            .filter(|s| s.write.is_some() || s.read.is_some())
+            .collect();
+        let reserved = register
+            .fields
+            .iter()
+            .fold(crate::svd::width_mask(register.size), |mask, f| {
+                mask & !f.mask()
+            });
+        if reserved != 0 {
+            semantics.push(Self::on_write(reserved, WriteAction::Ignore));
+        }
+        semantics
+    }
 }
 
 /// List of [`RegisterAccess`]'s where **`READ`** accesses are run-length-encoded.
-#[derive(Debug, Clone, Default)]
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RegmockLog {
     /// List of register accesses with run-length-encoded **`READ`** access.
     pub log: Vec<(RegisterAccess, usize)>,
+    /// List of accesses that violated the rules enforced by [`Regmock`],
+    /// see [`Regmock::access_policy`].
+    pub violations: Vec<AccessViolation>,
+    /// Entries and exits of ISRs, see [`Regmock::interrupts`].
+    pub interrupts: Vec<InterruptEvent>,
 }
 
 impl RegmockLog {
-    // Add new log entry to the log. Reads accesses are run-length-encoded.
+    // Add new log entry to the log. Reads accesses are run-length-encoded,
+    // the encoded entry keeps the sequence number and time of the first read
+    // and the times of the last read.
     pub(crate) fn push_log_entry(&mut self, entry: RegisterAccess) {
         match self.log.last_mut() {
-            Some(ref mut last) => {
+            Some(ref mut last)
                 if entry
                     .ty
                     .as_ref()
                     .is_some_and(|ty| *ty == RegisterAccessType::READ)
-                    && last.0 == entry
-                {
-                    last.1 += 1;
-                } else {
-                    self.log.push((entry, 1));
-                }
+                    && entry == last.0
+                    && entry.thread == last.0.thread =>
+            {
+                last.0.last_time = entry.last_time;
+                last.0.last_wall_time = entry.last_wall_time;
+                last.1 += 1;
             }
-            None => {
+            _ => {
                 self.log.push((entry, 1));
             }
         }
@@ -251,6 +510,23 @@ pub fn iter_full(&self) -> IterRegmockLogDecoded<'_> {
         }
     }
 
+    /// Get an iterator over **all** recorded accesses performed by `thread`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.write_volatile(0x100, 4, 0x1);
+    /// let main = std::thread::current().id();
+    /// assert_eq!(mock.log.iter_thread(main).count(), 1);
+    /// ```
+    pub fn iter_thread(&self, thread: ThreadId) -> impl Iterator<Item = &RegisterAccess> {
+        self.iter_full()
+            .filter(move |access| access.thread == Some(thread))
+    }
+
     /// Count number of **all** recorded register accesses in the log.
     pub fn len_full(&self) -> usize {
         self.iter_full().count()
@@ -259,6 +535,7 @@ pub fn len_full(&self) -> usize {
 
 /// Iterator over [`RegisterAccess`] values without sequences of duplicate
 /// **`READ`** entries (i.e. skips register polling accesses).
+#[derive(Clone)]
 pub struct IterRegmockLogNoPolling<'a> {
     inner: &'a RegmockLog,
     pos: usize,
@@ -284,6 +561,7 @@ fn next(&mut self) -> Option<Self::Item> {
 ///
 /// # Note
 /// This iterator skips entries in [`RegmockLog`] that have a run-length of 0.
+#[derive(Clone)]
 pub struct IterRegmockLogDecoded<'a> {
     inner: &'a RegmockLog,
     pos: usize,
@@ -313,6 +591,145 @@ fn next(&mut self) -> Option<Self::Item> {
     }
 }
 
+/// The bytes of a register that are targeted by an access.
+pub(crate) struct Lane {
+    /// Address of the register.
+    pub(crate) base: usize,
+    /// Width of the register in bytes.
+    pub(crate) width: usize,
+    /// Position of the accessed bytes in the register in bits.
+    shift: usize,
+    /// Accessed bits of the register.
+    pub(crate) mask: u64,
+    /// Offset of the access in the register in bytes.
+    offset: usize,
+    /// Width of the access in bytes.
+    len: usize,
+}
+
+impl Lane {
+    fn new(base: usize, width: usize, offset: usize, len: usize) -> Self {
+        let shift = offset * 8;
+        let register_mask = crate::svd::width_mask(width * 8);
+        let mask = if shift >= 64 {
+            0
+        } else {
+            (crate::svd::width_mask(len * 8) << shift) & register_mask
+        };
+        Self {
+            base,
+            width,
+            shift,
+            mask,
+            offset,
+            len,
+        }
+    }
+
+    /// Mask of all bits of the register.
+    fn register_mask(&self) -> u64 {
+        crate::svd::width_mask(self.width * 8)
+    }
+
+    /// Whether the access extends beyond the end of the register.
+    fn is_oversized(&self) -> bool {
+        self.offset
+            .checked_add(self.len)
+            .is_none_or(|end| end > self.width)
+    }
+
+    /// Get the accessed bytes from a register value.
+    pub(crate) fn extract(&self, register: u64) -> u64 {
+        (register & self.mask)
+            .checked_shr(self.shift as u32)
+            .unwrap_or(0)
+    }
+
+    /// Replace the accessed bytes of a register value with `value`.
+    pub(crate) fn insert(&self, register: u64, value: u64) -> u64 {
+        let value = value.checked_shl(self.shift as u32).unwrap_or(0);
+        (register & !self.mask) | (value & self.mask)
+    }
+}
+
+/// Address range a [`RangeReadFunction`] or [`RangeWriteFunction`] is registered for.
+///
+/// Describes `count` blocks of `size` bytes, each starting `stride` bytes
+/// after the previous one. A single block covers e.g. a whole peripheral,
+/// multiple blocks cover an array of register clusters.
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::utils::CallbackRange;
+/// // two clusters of 12 bytes, 16 bytes apart
+/// let range = CallbackRange::array(0x100, 0xc, 0x10, 2);
+/// assert!(range.locate(0x114).is_some());
+/// assert!(range.locate(0x10c).is_none());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallbackRange {
+    /// Address of the first block.
+    pub start: usize,
+    /// Size of each block in bytes.
+    pub size: usize,
+    /// Distance between the start addresses of two blocks in bytes.
+    pub stride: usize,
+    /// Number of blocks.
+    pub count: usize,
+}
+
+impl CallbackRange {
+    /// Range consisting of a single block of `size` bytes at `start`.
+    pub fn block(start: usize, size: usize) -> Self {
+        Self {
+            start,
+            size,
+            stride: size,
+            count: 1,
+        }
+    }
+
+    /// Range consisting of `count` blocks of `size` bytes, `stride` bytes apart.
+    pub fn array(start: usize, size: usize, stride: usize, count: usize) -> Self {
+        Self {
+            start,
+            size,
+            stride,
+            count,
+        }
+    }
+
+    /// Locate `addr` in the range, returns `None` if `addr` is not part of any block.
+    pub fn locate(&self, addr: usize) -> Option<RangeAccess> {
+        let relative = addr.checked_sub(self.start)?;
+        let index = relative.checked_div(self.stride).unwrap_or(0);
+        let offset = relative - index * self.stride;
+        (index < self.count && offset < self.size).then_some(RangeAccess {
+            addr,
+            index,
+            offset,
+        })
+    }
+}
+
+impl From<Range<usize>> for CallbackRange {
+    fn from(range: Range<usize>) -> Self {
+        Self::block(range.start, range.len())
+    }
+}
+
+/// Location of an access in a [`CallbackRange`], passed to range callbacks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RangeAccess {
+    /// Address of the accessed register.
+    pub addr: usize,
+    /// Index of the accessed block in the range.
+    pub index: usize,
+    /// Offset of the accessed register in its block in bytes.
+    pub offset: usize,
+}
+
 /// Type used to mock registers. Export to make typing in tests more readable.
 /// Used by [`Regmock`]
 pub type RegisterMap = HashMap<usize, u64>;
@@ -320,6 +737,157 @@ fn next(&mut self) -> Option<Self::Item> {
 pub type ReadFunction = Box<dyn FnMut(&mut RegisterMap, u64) -> u64 + Send>;
 /// Type of write-access callback functions that can be registered in [`Regmock::write_fn`].
 pub type WriteFunction = Box<dyn FnMut(&mut RegisterMap, u64, u64) -> u64 + Send>;
+/// Callback with access to the full context of a register access, see [`AccessContext`].
+///
+/// Registered in [`Regmock::read_callbacks`] or [`Regmock::write_callbacks`].
+/// Existing [`ReadFunction`]s and [`WriteFunction`]s can be converted into a
+/// [`Callback`] with [`From`].
+///
+/// # Examples
+///
+/// ```rust
+/// use regmock_rs::utils::{Callback, ReadFunction, Regmock};
+/// let mut mock = Regmock::default();
+/// mock.read_callbacks.insert(
+///     0x100,
+///     Callback::new(|ctx| ctx.before + ctx.len as u64),
+/// );
+/// let legacy: ReadFunction = Box::new(|_, before| before + 1);
+/// mock.read_callbacks.insert(0x104, legacy.into());
+///
+/// assert_eq!(mock.read_volatile(0x100, 2), 0x2);
+/// assert_eq!(mock.read_volatile(0x104, 4), 0x1);
+/// ```
+pub struct Callback(Box<dyn FnMut(&mut AccessContext) -> u64 + Send>);
+
+impl Callback {
+    /// Construct a [`Callback`] from a closure.
+    ///
+    /// The value returned from the closure is used as the value that was
+    /// read from or is written to the register.
+    pub fn new(f: impl FnMut(&mut AccessContext) -> u64 + Send + 'static) -> Self {
+        Self(Box::new(f))
+    }
+
+    fn call(&mut self, ctx: &mut AccessContext) -> u64 {
+        (self.0)(ctx)
+    }
+}
+
+impl From<ReadFunction> for Callback {
+    fn from(mut f: ReadFunction) -> Self {
+        Self::new(move |ctx| {
+            let before = ctx.before;
+            f(ctx.registers(), before)
+        })
+    }
+}
+
+impl From<WriteFunction> for Callback {
+    fn from(mut f: WriteFunction) -> Self {
+        Self::new(move |ctx| {
+            let (before, value) = (ctx.before, ctx.value);
+            f(ctx.registers(), before, value)
+        })
+    }
+}
+
+impl Debug for Callback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Callback")
+    }
+}
+
+/// Action scheduled by a [`Callback`] with [`AccessContext::schedule`].
+pub type DeferredAction = Box<dyn FnOnce(&mut Regmock) + Send>;
+
+/// Check registered with [`Regmock::at_end_of_test`], returns a description
+/// of the failure if the expectation is not met.
+pub type EndOfTestCheck = Box<dyn FnOnce(&Regmock) -> Result<(), String> + Send>;
+
+/// Context of a register access that is passed to a [`Callback`].
+///
+/// Gives access to the properties of the access as well as the registers,
+/// log and settings of the [`Regmock`] the access happens on.
+pub struct AccessContext<'a> {
+    /// Type of the access.
+    pub ty: RegisterAccessType,
+    /// Address the access was performed on.
+    pub addr: usize,
+    /// Width of the access in bytes.
+    pub len: usize,
+    /// Address of the accessed register, the callback is registered for.
+    pub register: usize,
+    /// Value of the register before the access.
+    pub before: u64,
+    /// Value that is written to the register. For reads, the same as
+    /// [`before`](#structfield.before).
+    pub value: u64,
+    mock: &'a mut Regmock,
+}
+
+impl AccessContext<'_> {
+    /// Get the mocked registers.
+    pub fn registers(&mut self) -> &mut RegisterMap {
+        &mut self.mock.register_mocks
+    }
+
+    /// Get the accesses logged before this access.
+    pub fn log(&self) -> &RegmockLog {
+        &self.mock.log
+    }
+
+    /// Get the name of the accessed register, see [`Regmock::get_reg_name`].
+    pub fn reg_name(&self) -> Option<&str> {
+        self.mock.get_reg_name(self.register)
+    }
+
+    /// Enable or disable logging of register accesses, including this one.
+    pub fn set_logging(&mut self, state: bool) {
+        self.mock.log_enabled = state;
+    }
+
+    /// Schedule `action` to be executed after this access has completed,
+    /// i.e. after it was logged and its value was stored.
+    ///
+    /// Register accesses performed by `action` on the [`Regmock`] are
+    /// handled like accesses through the PAC.
+    pub fn schedule(&mut self, action: impl FnOnce(&mut Regmock) + Send + 'static) {
+        self.mock.deferred.push(Box::new(action));
+    }
+
+    /// Get the current virtual time, see [`Regmock::now`].
+    pub fn now(&self) -> Duration {
+        self.mock.now
+    }
+
+    /// Raise interrupt `irq`, see [`Regmock::raise_interrupt`].
+    pub fn raise_interrupt(&mut self, irq: usize) {
+        self.mock.raise_interrupt(irq);
+    }
+
+    /// Schedule `action` to be executed once the virtual time advanced by
+    /// `delay`, see [`Regmock::schedule_in`].
+    pub fn schedule_in(
+        &mut self,
+        delay: Duration,
+        action: impl FnOnce(&mut Regmock) + Send + 'static,
+    ) {
+        self.mock.schedule_in(delay, action);
+    }
+}
+
+/// Type of `LDMST` callback functions that can be registered in [`Regmock::ldmst_fn`].
+///
+/// Gets passed the mocked registers, the value of the register before the
+/// access, the mask and the data of the `LDMST`. Returns the value that is
+/// written to the register.
+#[cfg(feature = "aurix")]
+pub type LdmstFunction = Box<dyn FnMut(&mut RegisterMap, u64, u64, u64) -> u64 + Send>;
+/// Type of read-access callback functions that can be registered in [`Regmock::read_range_fn`].
+pub type RangeReadFunction = Box<dyn FnMut(&mut RegisterMap, RangeAccess, u64) -> u64 + Send>;
+/// Type of write-access callback functions that can be registered in [`Regmock::write_range_fn`].
+pub type RangeWriteFunction = Box<dyn FnMut(&mut RegisterMap, RangeAccess, u64, u64) -> u64 + Send>;
 
 /// Mock and record register accesses of embedded devices.
 /// # Regmock
@@ -350,12 +918,24 @@ pub struct Regmock {
     /// Register mocks.
     ///
     /// ## Note:
-    /// Registers are initialized to 0x0 and not the chip specific initial
-    /// register value. Developers shall use an unrecorded call to `pac::REGISTER::init()`
-    /// or write the desired initial value directly into [`register_mocks`](#register_mocks)
-    /// object to have correct initial register values if absolutely needed.
+    /// Registers are initialized from [`reset_values`](#structfield.reset_values)
+    /// on their first access, or to 0x0 if no reset value is known.
+    /// Construct the mock with [`Regmock::from_svd`] to get the chip specific
+    /// reset values, or write the desired initial value directly into
+    /// [`register_mocks`](#structfield.register_mocks).
+    ///
+    /// Values are keyed by the base address of the register. Accesses to
+    /// single bytes of a register are merged into the value of the whole
+    /// register, whose width is taken from the [`database`](#structfield.database)
+    /// or from the widest aligned access to it.
     pub register_mocks: RegisterMap,
 
+    /// Reset values of the mocked registers.
+    ///
+    /// Used to initialize registers on their first access and by [`Regmock::reset`].
+    /// Registers without an entry reset to 0x0.
+    pub reset_values: RegisterMap,
+
     /// A map from register addresses to [`ReadFunction`] that gets called
     /// every time a specific register is *read* from through the PAC.
     ///
@@ -465,6 +1045,60 @@ pub struct Regmock {
     /// See [`Regmock::read_fn`] limitations section.
     pub write_fn: HashMap<usize, WriteFunction>,
 
+    /// A map of register addresses to [`Callback`]s that get called every
+    /// time a specific register is *read*.
+    ///
+    /// Like [`read_fn`](#structfield.read_fn), but the callback gets passed an
+    /// [`AccessContext`] with the properties of the access and access to the
+    /// registers, log and settings of the mock.
+    /// Takes precedence over [`read_fn`](#structfield.read_fn).
+    pub read_callbacks: HashMap<usize, Callback>,
+
+    /// A map of register addresses to [`Callback`]s that get called every
+    /// time a specific register is *written*.
+    ///
+    /// Like [`write_fn`](#structfield.write_fn), but the callback gets passed
+    /// an [`AccessContext`]. Takes precedence over [`write_fn`](#structfield.write_fn).
+    pub write_callbacks: HashMap<usize, Callback>,
+
+    /// A map of register addresses to [`LdmstFunction`]s that get called
+    /// every time a specific register is modified with an `LDMST` instruction.
+    ///
+    /// If no [`LdmstFunction`] exists for a register, the masked-merged value
+    /// `(before & !mask) | (data & mask)` is passed to the write callbacks of
+    /// the register (e.g. [`write_fn`](#structfield.write_fn)) like a regular write.
+    #[cfg(feature = "aurix")]
+    pub ldmst_fn: HashMap<usize, LdmstFunction>,
+
+    /// Read callbacks registered for a [`CallbackRange`] instead of a single
+    /// register, e.g. a whole peripheral or an array of register clusters.
+    ///
+    /// Called like a [`ReadFunction`] for reads of any register in the range
+    /// that has no callback in [`read_fn`](#structfield.read_fn). In addition
+    /// to the arguments of a [`ReadFunction`], the callback gets passed the
+    /// [`RangeAccess`] describing which register of the range is read.
+    ///
+    /// If multiple ranges contain a register, the first one registered is called.
+    ///
+    /// ## Examples
+    ///
+    /// ```rust,ignore
+    /// let cluster = CallbackRange::array(TIMER.timercluster()[0].ctrlstat().addr(), 0xc, 0xc, 2);
+    /// mock.read_range_fn.push((
+    ///     cluster,
+    ///     Box::new(|_, access: RangeAccess, before| before + access.index as u64),
+    /// ));
+    /// ```
+    pub read_range_fn: Vec<(CallbackRange, RangeReadFunction)>,
+
+    /// Write callbacks registered for a [`CallbackRange`] instead of a single
+    /// register.
+    ///
+    /// Called like a [`WriteFunction`] for writes to any register in the range
+    /// that has no callback in [`write_fn`](#structfield.write_fn).
+    /// See [`read_range_fn`](#structfield.read_range_fn).
+    pub write_range_fn: Vec<(CallbackRange, RangeWriteFunction)>,
+
     /// Controls if the register accesses get logged.
     /// Defaults to `true`.
     pub log_enabled: bool,
@@ -478,6 +1112,114 @@ pub struct Regmock {
     ///
     /// Consider using [`Regmock::get_reg_name`] which provides a simpler interface
     pub name_resolver: Option<Box<dyn Fn(u64) -> Option<&'static &'static str> + Send>>,
+
+    /// Metadata of the mocked registers (names, sizes, access rights, reset values
+    /// and bitfields).
+    ///
+    /// Takes precedence over [`name_resolver`](#structfield.name_resolver) when
+    /// resolving register names. Shared through an [`Arc`] so one database can
+    /// be loaded once and used by many tests.
+    pub database: Option<Arc<RegisterDatabase>>,
+
+    /// Hardware semantics of the bitfields of registers.
+    ///
+    /// Applied to every access that has callbacks enabled before the value
+    /// is stored and logged: write semantics are applied to the written value
+    /// before the [`WriteFunction`] is called, read side effects are applied
+    /// to the stored register value after the [`ReadFunction`] was called.
+    ///
+    /// Initialized from the bitfields in the [`database`](#structfield.database),
+    /// see [`FieldSemantics::from_register`].
+    pub field_semantics: HashMap<usize, Vec<FieldSemantics>>,
+
+    /// Controls if and how the access rights of registers and the
+    /// [`memory_map`](#structfield.memory_map) are enforced.
+    /// Defaults to [`AccessPolicy::Permissive`].
+    pub access_policy: AccessPolicy,
+
+    /// Address ranges that may be accessed. Accesses outside of these ranges
+    /// are reported as [`ViolationKind::Unmapped`] according to the
+    /// [`access_policy`](#structfield.access_policy).
+    ///
+    /// Defaults to `None`, i.e. any address may be accessed.
+    pub memory_map: Option<MemoryMap>,
+
+    /// Width in bytes of registers without metadata, inferred from the widest
+    /// aligned access to them.
+    register_widths: HashMap<usize, usize>,
+
+    /// Virtual time that passes on every **read** with callbacks enabled.
+    /// Defaults to zero, i.e. time only advances through [`Regmock::advance_time`].
+    pub read_cost: Duration,
+
+    /// Virtual time that passes on every **write** with callbacks enabled.
+    /// Defaults to zero, i.e. time only advances through [`Regmock::advance_time`].
+    pub write_cost: Duration,
+
+    /// Current virtual time.
+    now: Duration,
+
+    /// Number of accesses logged so far, the sequence number of the next access.
+    logged: u64,
+
+    /// Actions scheduled at a virtual time, ordered by time and insertion.
+    events: BTreeMap<(Duration, usize), DeferredAction>,
+
+    /// Number of actions scheduled so far, orders actions scheduled at the same time.
+    scheduled: usize,
+
+    /// Simulated interrupt controller.
+    ///
+    /// Interrupts raised by models and callbacks are dispatched to the
+    /// connected ISRs between register accesses performed through the PAC,
+    /// see [`crate::dispatch_interrupts`].
+    pub interrupts: InterruptController,
+
+    /// Attached peripheral models, detached models leave an empty slot so
+    /// the [`ModelHandle`]s of the other models stay valid.
+    models: Vec<Option<Box<dyn PeripheralModel>>>,
+
+    /// Actions scheduled by callbacks, executed after the current access.
+    deferred: Vec<DeferredAction>,
+
+    /// Addresses of registers written since the last reset, used to detect
+    /// repeated writes to **write-once** registers.
+    written_since_reset: HashSet<usize>,
+
+    /// Checks executed by [`Regmock::verify`] at the end of a test.
+    end_of_test: Vec<EndOfTestCheck>,
+
+    /// Expected accesses, checked on every access with callbacks enabled.
+    expectations: Vec<Expectation>,
+
+    /// Record the [`RegisterAccess::location`] of logged accesses.
+    ///
+    /// Accesses through the PAC are only located with the `backtrace`
+    /// feature. They capture a backtrace, which slows down the test, see
+    /// [`AccessLocation`]. The backtrace is only resolved to a source location
+    /// when the access is printed, e.g. by [`Regmock::format_access`].
+    /// Requires debug information. Defaults to `false`.
+    pub capture_locations: bool,
+
+    /// Frames of the backtrace whose symbol contains one of these strings
+    /// are skipped when resolving the location of an access through the PAC.
+    ///
+    /// Frames of `regmock_rs` and the standard library are always skipped.
+    /// Defaults to `["::common::"]`, the module of the register API in PACs
+    /// generated by `svd2pac`.
+    pub location_filter: Vec<String>,
+
+    /// Caller of the access function of this crate that performs the current access.
+    pub(crate) caller: Option<&'static Location<'static>>,
+
+    /// Recorded trace replayed by the mock.
+    ///
+    /// While a trace is replayed, every access with callbacks enabled is
+    /// checked against the next access of the trace. Reads return the value
+    /// of the trace instead of the mocked register value. An access that
+    /// diverges from the trace `panic!()`s, accesses of the trace that were
+    /// not replayed are reported by [`Regmock::verify`]. Defaults to `None`.
+    pub replay: Option<Replay>,
 }
 
 impl Debug for Regmock {
@@ -486,6 +1228,24 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
             .field("log", &self.log)
             .field("read_fn", &"TODO: HashMap<usize, ReadFunction>")
             .field("write_fn", &"TODO: HashMap<usize, WriteFunction>")
+            .field("read_callbacks", &self.read_callbacks.keys())
+            .field("write_callbacks", &self.write_callbacks.keys())
+            .field(
+                "read_range_fn",
+                &self
+                    .read_range_fn
+                    .iter()
+                    .map(|(r, _)| r)
+                    .collect::<Vec<_>>(),
+            )
+            .field(
+                "write_range_fn",
+                &self
+                    .write_range_fn
+                    .iter()
+                    .map(|(r, _)| r)
+                    .collect::<Vec<_>>(),
+            )
             .field("log_enabled", &self.log_enabled)
             .field("callback_enabled", &self.callback_enabled)
             .finish()
@@ -498,11 +1258,62 @@ fn default() -> Self {
         Self {
             log: Default::default(),
             register_mocks: Default::default(),
+            reset_values: Default::default(),
             read_fn: Default::default(),
             write_fn: Default::default(),
+            read_callbacks: Default::default(),
+            write_callbacks: Default::default(),
+            #[cfg(feature = "aurix")]
+            ldmst_fn: Default::default(),
+            read_range_fn: Default::default(),
+            write_range_fn: Default::default(),
             log_enabled: true,
             callback_enabled: true,
             name_resolver: None,
+            database: None,
+            field_semantics: Default::default(),
+            access_policy: AccessPolicy::Permissive,
+            memory_map: None,
+            register_widths: Default::default(),
+            read_cost: Duration::ZERO,
+            write_cost: Duration::ZERO,
+            now: Duration::ZERO,
+            logged: 0,
+            events: Default::default(),
+            scheduled: 0,
+            interrupts: Default::default(),
+            models: Default::default(),
+            deferred: Default::default(),
+            written_since_reset: Default::default(),
+            end_of_test: Default::default(),
+            expectations: Default::default(),
+            capture_locations: false,
+            location_filter: vec!["::common::".to_owned()],
+            caller: None,
+            replay: None,
+        }
+    }
+}
+
+impl Drop for Regmock {
+    /// Verify the mock, see [`Regmock::verify`].
+    ///
+    /// # Panics
+    ///
+    /// Will panic if an expectation or end of test check is not met, unless
+    /// the thread is already panicking. Mocks dropped while the thread exits,
+    /// e.g. one set with [`crate::init_regmock`], can't fail the test and
+    /// only print the failures.
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            return;
+        }
+        if let Err(failures) = self.verify() {
+            let message = format!("Regmock verification failed:\n{}", failures.join("\n"));
+            if crate::MOCK.try_with(|_| ()).is_ok() {
+                panic!("{message}");
+            }
+            eprintln!("{message}");
         }
     }
 }
@@ -512,45 +1323,818 @@ pub fn with_resolver<T>(resolver: &'static T) -> Self
     where
         T: Fn(u64) -> Option<&'static &'static str> + Send + Sync,
     {
-        Self {
-            log: Default::default(),
-            register_mocks: Default::default(),
-            read_fn: Default::default(),
-            write_fn: Default::default(),
-            log_enabled: true,
-            callback_enabled: true,
-            name_resolver: Some(Box::new(resolver)),
+        let mut mock = Self::default();
+        mock.name_resolver = Some(Box::new(resolver));
+        mock
+    }
+
+    /// Construct a [`Regmock`] with the metadata and reset values of all
+    /// registers described in the SVD file at `path`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// let mock = Regmock::from_svd("path/to/chip.svd").unwrap();
+    /// init_regmock(Arc::new(Mutex::new(mock)));
+    /// ```
+    pub fn from_svd(path: impl AsRef<Path>) -> Result<Self, SvdError> {
+        Ok(Self::with_database(RegisterDatabase::from_svd(path)?))
+    }
+
+    /// Construct a [`Regmock`] with the metadata and reset values of all
+    /// registers described in the SVD passed as string.
+    ///
+    /// See [`Regmock::from_svd`].
+    pub fn from_svd_str(svd: &str) -> Result<Self, SvdError> {
+        Ok(Self::with_database(RegisterDatabase::from_svd_str(svd)?))
+    }
+
+    /// Construct a [`Regmock`] that uses `database` for register names and
+    /// initializes [`reset_values`](#structfield.reset_values) from it.
+    pub fn with_database(database: impl Into<Arc<RegisterDatabase>>) -> Self {
+        let database = database.into();
+        let mut mock = Self::default();
+        mock.reset_values = database
+            .iter()
+            .map(|r| (r.address, r.reset_value))
+            .collect();
+        mock.field_semantics = database
+            .iter()
+            .map(|r| (r.address, FieldSemantics::from_register(r)))
+            .filter(|(_, semantics)| !semantics.is_empty())
+            .collect();
+        mock.database = Some(database);
+        mock
+    }
+
+    /// Simulate a chip reset by restoring the reset value of every register.
+    ///
+    /// The log, callbacks and settings of the [`Regmock`] are not modified.
+    /// Pending interrupts are cleared and attached models are notified with
+    /// [`PeripheralModel::on_reset`].
+    /// The virtual time and scheduled actions are not affected.
+    pub fn reset(&mut self) {
+        self.register_mocks.clear();
+        self.written_since_reset.clear();
+        self.interrupts.clear_all();
+        for model in self.models.iter_mut().flatten() {
+            model.on_reset(&mut self.register_mocks);
         }
     }
 
-    fn get_reg_value(&mut self, addr: usize) -> u64 {
-        let register_mocks = &mut self.register_mocks;
-        if let std::collections::hash_map::Entry::Vacant(e) = register_mocks.entry(addr) {
-            e.insert(0u64);
-            0u64
-        } else {
-            *register_mocks.get(&addr).unwrap()
+    /// Construct a [`Regmock`] like [`Regmock::with_database`] that enforces
+    /// the access rights of the registers according to `policy`.
+    pub fn strict(database: impl Into<Arc<RegisterDatabase>>, policy: AccessPolicy) -> Self {
+        let mut mock = Self::with_database(database);
+        mock.access_policy = policy;
+        mock
+    }
+
+    /// Check an access against the [`memory_map`](#structfield.memory_map) and
+    /// the access rights of the register.
+    fn access_violation(
+        &self,
+        ty: &RegisterAccessType,
+        addr: usize,
+        len: usize,
+        lane: &Lane,
+    ) -> Option<ViolationKind> {
+        if self.access_policy == AccessPolicy::Permissive || !self.callback_enabled {
+            return None;
+        }
+        if let Some(memory_map) = &self.memory_map {
+            if !memory_map.contains(addr, len) {
+                let nearest = self
+                    .database
+                    .as_ref()
+                    .and_then(|db| db.nearest(addr))
+                    .map(|r| r.name.clone())
+                    .or_else(|| memory_map.nearest(addr).map(|r| r.name.clone()));
+                return Some(ViolationKind::Unmapped { nearest });
+            }
+        }
+        if len == 0 || !addr.is_multiple_of(len) {
+            return Some(ViolationKind::Misaligned);
+        }
+        if lane.is_oversized() {
+            return Some(ViolationKind::Oversized {
+                register_size: lane.width,
+            });
+        }
+        let access = self.database.as_ref()?.get(lane.base)?.access;
+        match ty {
+            RegisterAccessType::READ if !access.is_readable() => {
+                Some(ViolationKind::ReadFromWriteOnly)
+            }
+            RegisterAccessType::READ => None,
+            _ if !access.is_writable() => Some(ViolationKind::WriteToReadOnly),
+            _ if matches!(access, Access::WriteOnce | Access::ReadWriteOnce)
+                && self.written_since_reset.contains(&lane.base) =>
+            {
+                Some(ViolationKind::RepeatedWriteOnce)
+            }
+            _ => None,
+        }
+    }
+
+    /// Apply the write semantics of the register at `addr` to a written value.
+    ///
+    /// Only the bits in `lane_mask` were written, all other bits of `val`
+    /// hold the value of the register before the write.
+    fn apply_write_semantics(&self, addr: usize, before: u64, val: u64, lane_mask: u64) -> u64 {
+        match self.field_semantics.get(&addr) {
+            Some(fields) if self.callback_enabled => fields
+                .iter()
+                .filter_map(|f| f.write.map(|action| (f.mask & lane_mask, action)))
+                .fold(val, |value, (mask, action)| {
+                    (value & !mask) | action.apply(mask, before, val)
+                }),
+            _ => val,
+        }
+    }
+
+    /// Apply the read side effects of the register at `addr` to its stored value.
+    ///
+    /// Only bits in `lane_mask` were read and are affected.
+    fn apply_read_semantics(&mut self, addr: usize, lane_mask: u64) {
+        let Some(fields) = self.field_semantics.get(&addr) else {
+            return;
+        };
+        if !self.callback_enabled {
+            return;
+        }
+        let current = self.register_mocks.get(&addr).copied().unwrap_or(0);
+        let updated = fields
+            .iter()
+            .filter_map(|f| f.read.map(|action| (f.mask & lane_mask, action)))
+            .fold(current, |value, (mask, action)| {
+                (value & !mask) | action.apply(mask, current)
+            });
+        self.register_mocks.insert(addr, updated);
+    }
+
+    /// Record or `panic!()` on a violating access depending on the [`AccessPolicy`].
+    ///
+    /// Returns `true` if the access shall be performed anyway.
+    fn report_violation(&mut self, kind: ViolationKind, access: RegisterAccess) -> bool {
+        if self.access_policy == AccessPolicy::Panic {
+            panic!(
+                "Register access violation {:?}: {}",
+                kind,
+                self.format_access(&access)
+            );
+        }
+        self.log.violations.push(AccessViolation {
+            kind,
+            access,
+            log_index: self.log.log.len(),
+        });
+        self.access_policy != AccessPolicy::Drop
+    }
+
+    /// Determine the register and its bytes an access of `len` bytes at `addr` targets.
+    ///
+    /// The register is looked up in the [`database`](#structfield.database) first.
+    /// Without metadata, a previously accessed register that covers `addr` is
+    /// used, otherwise the access defines a new register of `len` bytes at `addr`.
+    /// An aligned access that is wider than the registers it covers widens
+    /// them into one register, keeping their values.
+    fn lane(&mut self, addr: usize, len: usize) -> Lane {
+        if let Some(register) = self.database.as_ref().and_then(|db| db.containing(addr)) {
+            return Lane::new(
+                register.address,
+                register.size.div_ceil(8),
+                addr - register.address,
+                len,
+            );
         }
+        let (base, width) = match self.inferred_register(addr) {
+            Some((base, width))
+                if (addr - base)
+                    .checked_add(len)
+                    .is_some_and(|end| end <= width) =>
+            {
+                (base, width)
+            }
+            Some((base, width)) if base != addr || len == 0 || !addr.is_multiple_of(len) => {
+                (base, width)
+            }
+            _ => {
+                self.widen_register(addr, len);
+                (addr, len)
+            }
+        };
+        Lane::new(base, width, addr - base, len)
+    }
+
+    /// Get the address and width in bytes of the register without metadata
+    /// that was inferred from earlier accesses and covers `addr`.
+    fn inferred_register(&self, addr: usize) -> Option<(usize, usize)> {
+        (addr.saturating_sub(7)..=addr).rev().find_map(|base| {
+            self.register_widths
+                .get(&base)
+                .filter(|width| **width > addr - base)
+                .map(|width| (base, *width))
+        })
+    }
+
+    /// Determine the register and its bytes each access of `log` targets,
+    /// like [`Regmock::lane`] does, after all accesses were made.
+    ///
+    /// Registers without metadata are inferred from the accesses of the log,
+    /// an access to a register that a later access widens targets the widened
+    /// register.
+    pub(crate) fn lanes_of(&self, log: &RegmockLog) -> Vec<Option<Lane>> {
+        let mut inferred = Regmock::default();
+        inferred.database = self.database.clone();
+        for (access, _) in &log.log {
+            if let Some(addr) = access.addr {
+                inferred.lane(addr, access.len.unwrap_or(4));
+            }
+        }
+        log.log
+            .iter()
+            .map(|(access, _)| {
+                let addr = access.addr?;
+                let len = access.len.unwrap_or(4);
+                let (base, width) = inferred
+                    .database
+                    .as_ref()
+                    .and_then(|db| db.containing(addr))
+                    .map(|register| (register.address, register.size.div_ceil(8)))
+                    .or_else(|| inferred.inferred_register(addr))?;
+                Some(Lane::new(base, width, addr - base, len))
+            })
+            .collect()
+    }
+
+    /// Define a register of `len` bytes at `addr` without metadata, merging
+    /// the registers inferred from earlier accesses to its bytes into it.
+    fn widen_register(&mut self, addr: usize, len: usize) {
+        let mut merged = None;
+        for base in addr..addr.saturating_add(len) {
+            let Some(width) = self.register_widths.remove(&base) else {
+                continue;
+            };
+            let value = self.peek_reg_value(base) & crate::svd::width_mask(width * 8);
+            self.register_mocks.remove(&base);
+            let shift = (base - addr) * 8;
+            if shift < 64 {
+                merged = Some(merged.unwrap_or(0) | value << shift);
+            }
+        }
+        self.register_widths.insert(addr, len);
+        if let Some(value) = merged {
+            self.register_mocks
+                .insert(addr, value & crate::svd::width_mask(len * 8));
+        }
+    }
+
+    /// Get the current value of a register without initializing it.
+    fn peek_reg_value(&self, addr: usize) -> u64 {
+        self.register_mocks
+            .get(&addr)
+            .or_else(|| self.reset_values.get(&addr))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    fn get_reg_value(&mut self, addr: usize) -> u64 {
+        *self
+            .register_mocks
+            .entry(addr)
+            .or_insert_with(|| self.reset_values.get(&addr).copied().unwrap_or(0))
     }
     /// Execute the register specific `read_fn` callback/closure thing if there
     /// exists one for the current register.
-    fn exec_read_fn(&mut self, addr: usize, before: u64) -> u64 {
-        if !self.read_fn.contains_key(&addr) || !self.callback_enabled {
-            before
-        } else {
-            let cb = self.read_fn.get_mut(&addr).unwrap();
-            cb(&mut self.register_mocks, before)
+    ///
+    /// Callbacks registered for the exact address take precedence over
+    /// attached models, which take precedence over callbacks registered for
+    /// an address range.
+    fn exec_read_fn(&mut self, lane: &Lane, before: u64) -> u64 {
+        let addr = lane.base;
+        if !self.callback_enabled {
+            return before;
+        }
+        if self.read_callbacks.contains_key(&addr) {
+            return self.exec_callback(RegisterAccessType::READ, lane, before, before);
+        }
+        if let Some(cb) = self.read_fn.get_mut(&addr) {
+            return cb(&mut self.register_mocks, before);
+        }
+        if let Some(index) = self.model_at(addr) {
+            return self.exec_model(index, RegisterAccessType::READ, lane, before, before);
+        }
+        match self
+            .read_range_fn
+            .iter_mut()
+            .find_map(|(range, cb)| range.locate(addr).map(|access| (access, cb)))
+        {
+            Some((access, cb)) => cb(&mut self.register_mocks, access, before),
+            None => before,
         }
     }
 
     /// Execute the register specific `read_fn` callback/closure thing if there
     /// exists one for the current register.
-    fn exec_write_fn(&mut self, addr: usize, before: u64, val: u64) -> u64 {
-        if !self.write_fn.contains_key(&addr) || !self.callback_enabled {
-            val
+    ///
+    /// Callbacks registered for the exact address take precedence over
+    /// attached models, which take precedence over callbacks registered for
+    /// an address range.
+    ///
+    /// `ty` is the type of the access passed to [`Callback`]s and models, e.g.
+    /// to distinguish atomic accesses from regular writes.
+    fn exec_write_fn(&mut self, ty: RegisterAccessType, lane: &Lane, before: u64, val: u64) -> u64 {
+        let addr = lane.base;
+        if !self.callback_enabled {
+            return val;
+        }
+        if self.write_callbacks.contains_key(&addr) {
+            return self.exec_callback(ty, lane, before, val);
+        }
+        if let Some(cb) = self.write_fn.get_mut(&addr) {
+            return cb(&mut self.register_mocks, before, val);
+        }
+        if let Some(index) = self.model_at(addr) {
+            return self.exec_model(index, ty, lane, before, val);
+        }
+        match self
+            .write_range_fn
+            .iter_mut()
+            .find_map(|(range, cb)| range.locate(addr).map(|access| (access, cb)))
+        {
+            Some((access, cb)) => cb(&mut self.register_mocks, access, before, val),
+            None => val,
+        }
+    }
+
+    /// Execute the [`Callback`] registered for the register of `lane`.
+    ///
+    /// The callback is removed from its map while it is executed, so it
+    /// can be given mutable access to the [`Regmock`].
+    fn exec_callback(
+        &mut self,
+        ty: RegisterAccessType,
+        lane: &Lane,
+        before: u64,
+        value: u64,
+    ) -> u64 {
+        let callbacks = match ty {
+            RegisterAccessType::READ => &mut self.read_callbacks,
+            _ => &mut self.write_callbacks,
+        };
+        let Some(mut cb) = callbacks.remove(&lane.base) else {
+            return value;
+        };
+        let result = self.with_context(ty.clone(), lane, before, value, |ctx| cb.call(ctx));
+        let callbacks = match ty {
+            RegisterAccessType::READ => &mut self.read_callbacks,
+            _ => &mut self.write_callbacks,
+        };
+        callbacks.entry(lane.base).or_insert(cb);
+        result
+    }
+
+    /// Route an access to the attached model in slot `index`.
+    ///
+    /// Like callbacks, the model is taken out of its slot while it is executed.
+    fn exec_model(
+        &mut self,
+        index: usize,
+        ty: RegisterAccessType,
+        lane: &Lane,
+        before: u64,
+        value: u64,
+    ) -> u64 {
+        let Some(mut model) = self.models[index].take() else {
+            return value;
+        };
+        let result = self.with_context(ty.clone(), lane, before, value, |ctx| match ty {
+            RegisterAccessType::READ => model.on_read(ctx),
+            _ => model.on_write(ctx),
+        });
+        self.models[index].get_or_insert(model);
+        result
+    }
+
+    /// Construct the [`AccessContext`] of an access to the register of `lane`
+    /// and pass it to `f`.
+    fn with_context<R>(
+        &mut self,
+        ty: RegisterAccessType,
+        lane: &Lane,
+        before: u64,
+        value: u64,
+        f: impl FnOnce(&mut AccessContext) -> R,
+    ) -> R {
+        f(&mut AccessContext {
+            ty,
+            addr: lane.base + lane.offset,
+            len: lane.len,
+            register: lane.base,
+            before,
+            value,
+            mock: self,
+        })
+    }
+
+    /// Get the slot of the first attached model whose range contains `addr`.
+    fn model_at(&self, addr: usize) -> Option<usize> {
+        self.models.iter().position(|model| {
+            model
+                .as_ref()
+                .is_some_and(|model| model.range().contains(&addr))
+        })
+    }
+
+    /// Attach a [`PeripheralModel`] to the mock.
+    ///
+    /// The mock takes ownership of the model and routes all accesses to
+    /// registers in [`PeripheralModel::range`] to it, unless a callback is
+    /// registered for the exact address of the register. If the ranges of
+    /// multiple models overlap, the model attached first is used.
+    ///
+    /// The returned [`ModelHandle`] gives access to the model afterwards,
+    /// see [`Regmock::model`].
+    pub fn attach_model<M: PeripheralModel>(&mut self, model: M) -> ModelHandle<M> {
+        self.models.push(Some(Box::new(model)));
+        ModelHandle::new(self.models.len() - 1)
+    }
+
+    /// Detach a model from the mock and return it.
+    ///
+    /// Returns `None` if the model was already detached.
+    pub fn detach_model<M: PeripheralModel>(&mut self, handle: ModelHandle<M>) -> Option<M> {
+        let model: Box<dyn Any> = self.models.get_mut(handle.index)?.take()?;
+        model.downcast().ok().map(|model| *model)
+    }
+
+    /// Get a reference to an attached model, e.g. to inspect its state.
+    ///
+    /// Returns `None` if the model was detached.
+    pub fn model<M: PeripheralModel>(&self, handle: &ModelHandle<M>) -> Option<&M> {
+        let model: &dyn Any = self.models.get(handle.index)?.as_deref()?;
+        model.downcast_ref()
+    }
+
+    /// Get a mutable reference to an attached model, e.g. to modify its state.
+    ///
+    /// Returns `None` if the model was detached.
+    pub fn model_mut<M: PeripheralModel>(&mut self, handle: &ModelHandle<M>) -> Option<&mut M> {
+        let model: &mut dyn Any = self.models.get_mut(handle.index)?.as_deref_mut()?;
+        model.downcast_mut()
+    }
+
+    /// Notify all attached models that the virtual time advanced by `elapsed`.
+    fn tick(&mut self, elapsed: Duration) {
+        if elapsed.is_zero() {
+            return;
+        }
+        self.now += elapsed;
+        for index in 0..self.models.len() {
+            if let Some(mut model) = self.models[index].take() {
+                model.on_tick(self, elapsed);
+                self.models[index].get_or_insert(model);
+            }
+        }
+    }
+
+    /// Raise interrupt `irq` of the [`interrupts`](#structfield.interrupts) controller.
+    pub fn raise_interrupt(&mut self, irq: usize) {
+        self.interrupts.raise(irq);
+    }
+
+    /// Get the next pending and enabled interrupt and mark it as active.
+    ///
+    /// Returns `None` while callbacks are disabled or another ISR is running.
+    /// The caller has to call the returned ISR and [`Regmock::exit_interrupt`]
+    /// afterwards, see [`crate::dispatch_interrupts`].
+    pub fn enter_interrupt(&mut self) -> Option<(usize, Isr)> {
+        if !self.callback_enabled {
+            return None;
+        }
+        let (irq, isr) = self.interrupts.next(|addr| self.peek_reg_value(addr))?;
+        self.interrupts.enter(irq);
+        self.push_interrupt_event(irq, InterruptEventKind::Enter);
+        Some((irq, isr))
+    }
+
+    /// Mark the ISR of the active interrupt as finished.
+    pub fn exit_interrupt(&mut self) {
+        if let Some(irq) = self.interrupts.active() {
+            self.interrupts.exit();
+            self.push_interrupt_event(irq, InterruptEventKind::Exit);
+        }
+    }
+
+    fn push_interrupt_event(&mut self, irq: usize, kind: InterruptEventKind) {
+        self.log.interrupts.push(InterruptEvent {
+            irq,
+            kind,
+            log_index: self.log.log.len(),
+            time: self.now,
+        });
+    }
+
+    /// Get the current virtual time.
+    ///
+    /// The virtual time starts at zero and advances by
+    /// [`read_cost`](#structfield.read_cost) and [`write_cost`](#structfield.write_cost)
+    /// on every access and explicitly through [`Regmock::advance_time`].
+    pub fn now(&self) -> Duration {
+        self.now
+    }
+
+    /// Advance the virtual time by `duration`.
+    ///
+    /// Actions scheduled up to the new time are executed in order. Before
+    /// each action, the attached models are notified of the time elapsed
+    /// since the previous step with [`PeripheralModel::on_tick`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.schedule_in(Duration::from_millis(10), |mock| {
+    ///     mock.register_mocks.insert(0x100, 0x1);
+    /// });
+    /// mock.advance_time(Duration::from_millis(9));
+    /// assert_eq!(mock.read_volatile(0x100, 4), 0x0);
+    /// mock.advance_time(Duration::from_millis(1));
+    /// assert_eq!(mock.read_volatile(0x100, 4), 0x1);
+    /// ```
+    pub fn advance_time(&mut self, duration: Duration) {
+        let target = self.now + duration;
+        while let Some(entry) = self.events.first_entry() {
+            let at = entry.key().0;
+            if at > target {
+                break;
+            }
+            let action = entry.remove();
+            self.tick(at.saturating_sub(self.now));
+            action(self);
+            self.run_deferred();
+        }
+        self.tick(target.saturating_sub(self.now));
+    }
+
+    /// Schedule `action` to be executed when the virtual time reaches `at`.
+    ///
+    /// Actions scheduled in the past are executed on the next advance of
+    /// the virtual time. Actions scheduled for the same time are executed
+    /// in the order they were scheduled.
+    pub fn schedule_at(
+        &mut self,
+        at: Duration,
+        action: impl FnOnce(&mut Regmock) + Send + 'static,
+    ) {
+        self.events.insert((at, self.scheduled), Box::new(action));
+        self.scheduled += 1;
+    }
+
+    /// Schedule `action` to be executed once the virtual time advanced by `delay`.
+    pub fn schedule_in(
+        &mut self,
+        delay: Duration,
+        action: impl FnOnce(&mut Regmock) + Send + 'static,
+    ) {
+        self.schedule_at(self.now + delay, action);
+    }
+
+    /// Add an access to the log, stamped with its sequence number, virtual and
+    /// wall-clock time, thread and location, and check it against the expectations.
+    #[track_caller]
+    fn log_access(&mut self, mut access: RegisterAccess) {
+        let caller = match self.caller {
+            Some(caller) => caller,
+            None => Location::caller(),
+        };
+        let wall_time = SystemTime::now();
+        access.time = Some(self.now);
+        access.last_time = Some(self.now);
+        access.wall_time = Some(wall_time);
+        access.last_wall_time = Some(wall_time);
+        access.thread = Some(std::thread::current().id());
+        if self.capture_locations && (self.log_enabled || self.callback_enabled) {
+            access.location = AccessLocation::capture(caller, &self.location_filter);
+        }
+        if self.log_enabled {
+            access.seq = Some(self.logged);
+            self.logged += 1;
+            self.log.push_log_entry(access.clone());
+        }
+        if self.callback_enabled {
+            self.check_expectations(&access, caller);
+        }
+    }
+
+    /// Let the cost of an access pass after it completed.
+    fn finish_access(&mut self, cost: Duration) {
+        self.run_deferred();
+        if self.callback_enabled && !cost.is_zero() {
+            self.advance_time(cost);
+        }
+    }
+
+    /// Execute the actions scheduled by callbacks during the last access.
+    fn run_deferred(&mut self) {
+        while !self.deferred.is_empty() {
+            for action in std::mem::take(&mut self.deferred) {
+                action(self);
+            }
+        }
+    }
+
+    /// Register `check` to be executed by [`Regmock::verify`] at the end of
+    /// the test, e.g. to check the final register state or the access log.
+    ///
+    /// Tests using the `#[regmock_rs::test]` attribute verify the mock
+    /// automatically, see [`crate::harness::TestHarness`].
+    pub fn at_end_of_test(
+        &mut self,
+        check: impl FnOnce(&Regmock) -> Result<(), String> + Send + 'static,
+    ) {
+        self.end_of_test.push(Box::new(check));
+    }
+
+    /// Execute and remove all checks registered with [`Regmock::at_end_of_test`]
+    /// and all expectations, see [`Regmock::expect_write`]. Stops the
+    /// [`replay`](#structfield.replay) of a trace.
+    ///
+    /// Returns the descriptions of all failed checks, unmet expectations and
+    /// accesses of the replayed trace that did not happen.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.at_end_of_test(|mock| match mock.register_mocks.get(&0x100) {
+    ///     Some(0x1) => Ok(()),
+    ///     _ => Err("0x100 was not set".to_string()),
+    /// });
+    /// assert_eq!(mock.verify(), Err(vec!["0x100 was not set".to_string()]));
+    /// assert_eq!(mock.verify(), Ok(()));
+    /// ```
+    pub fn verify(&mut self) -> Result<(), Vec<String>> {
+        let checks = std::mem::take(&mut self.end_of_test);
+        // the mock that names registers while the checks run is verified
+        // as well when it is dropped, but has no checks
+        let mut failures: Vec<_> = if checks.is_empty() {
+            Vec::new()
         } else {
-            let cb = self.write_fn.get_mut(&addr).unwrap();
-            cb(&mut self.register_mocks, before, val)
+            crate::matchers::with_names_from(self, |mock| {
+                checks
+                    .into_iter()
+                    .filter_map(|check| check(mock).err())
+                    .collect()
+            })
+        };
+        failures.extend(self.unfinished_replay());
+        self.replay = None;
+        failures.extend(
+            std::mem::take(&mut self.expectations)
+                .into_iter()
+                .filter(|expectation| !expectation.is_satisfied())
+                .map(|expectation| {
+                    format!(
+                        "unmet expectation: {expectation}, got {} time(s)",
+                        expectation.calls()
+                    )
+                }),
+        );
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
+
+    /// Expect reads of the register at `addr`.
+    ///
+    /// While a mock has expectations for reads of a register, each read of it
+    /// has to match one of them, otherwise the access `panic!()`s. Registers
+    /// without expectations can be accessed freely. Unmet expectations are
+    /// reported by [`Regmock::verify`].
+    #[track_caller]
+    pub fn expect_read(&mut self, addr: usize) -> &mut Expectation {
+        self.expect(ExpectedAccess::Read, addr..addr + 1)
+    }
+
+    /// Expect writes to the register at `addr`.
+    ///
+    /// While a mock has expectations for writes to a register, each write to
+    /// it has to match one of them, otherwise the access `panic!()`s.
+    /// Registers without expectations can be accessed freely. Unmet
+    /// expectations are reported by [`Regmock::verify`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::Regmock;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.expect_write(0x100).with_value(0x1).times(2);
+    /// mock.write_volatile(0x100, 4, 0x1);
+    /// mock.write_volatile(0x100, 4, 0x1);
+    /// assert_eq!(mock.verify(), Ok(()));
+    /// ```
+    ///
+    /// ```rust,should_panic
+    /// # use regmock_rs::utils::Regmock;
+    /// let mut mock = Regmock::default();
+    /// mock.expect_write(0x100).with_value(0x1);
+    /// mock.write_volatile(0x100, 4, 0x2); // panics
+    /// ```
+    #[track_caller]
+    pub fn expect_write(&mut self, addr: usize) -> &mut Expectation {
+        self.expect(ExpectedAccess::Write, addr..addr + 1)
+    }
+
+    /// Expect no access to any address in `range`.
+    #[track_caller]
+    pub fn expect_no_access(&mut self, range: Range<usize>) -> &mut Expectation {
+        self.expect(ExpectedAccess::Any, range).never()
+    }
+
+    #[track_caller]
+    fn expect(&mut self, access: ExpectedAccess, range: Range<usize>) -> &mut Expectation {
+        self.expectations.push(Expectation::new(
+            access,
+            range,
+            std::panic::Location::caller(),
+        ));
+        self.expectations.last_mut().unwrap()
+    }
+
+    /// Count `access` towards the first expectation that accepts it.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if there are expectations for the access but none of them
+    /// accepts it, or if it happens out of the order of its sequence.
+    fn check_expectations(&mut self, access: &RegisterAccess, caller: &'static Location<'static>) {
+        let mut applicable = self
+            .expectations
+            .iter()
+            .enumerate()
+            .filter(|(_, e)| e.applies_to(access))
+            .peekable();
+        if applicable.peek().is_none() {
+            return;
+        }
+        let Some(index) = applicable
+            .clone()
+            .find(|(_, e)| e.accepts(access))
+            .map(|(index, _)| index)
+        else {
+            let expected: Vec<_> = applicable.map(|(_, e)| format!("  {e}")).collect();
+            panic!(
+                "Unexpected register access {}, expected:\n{}",
+                self.format_offending_access(access, caller),
+                expected.join("\n")
+            );
+        };
+
+        let expectation = &self.expectations[index];
+        if let Some((sequence, position)) = expectation.sequence() {
+            let current = sequence.current();
+            let unmet = self.expectations.iter().find(|e| {
+                e.sequence().is_some_and(|(s, p)| {
+                    s.is(sequence) && (current..*position).contains(p) && !e.is_satisfied()
+                })
+            });
+            if *position < current || unmet.is_some() {
+                let before =
+                    unmet.map_or_else(|| "a later expectation".to_string(), |e| e.to_string());
+                panic!(
+                    "Register access {} out of sequence: {expectation} happened before {before}",
+                    self.format_offending_access(access, caller),
+                );
+            }
+            sequence.advance(*position);
+        }
+        self.expectations[index].call();
+    }
+
+    /// Render an access that failed a check with its sequence number and
+    /// location, which is captured for it if it was not recorded already.
+    fn format_offending_access(
+        &self,
+        access: &RegisterAccess,
+        caller: &'static Location<'static>,
+    ) -> String {
+        let mut access = access.clone();
+        if access.location.is_none() {
+            access.location = AccessLocation::capture(caller, &self.location_filter);
+        }
+        match access.seq {
+            Some(seq) => format!("{} (seq {seq})", self.format_access(&access)),
+            None => self.format_access(&access),
         }
     }
 
@@ -564,11 +2148,87 @@ pub fn get_logs(&self) -> RegmockLog {
         self.log.clone()
     }
 
-    pub fn get_reg_name(&self, addr: usize) -> Option<&'static str> {
-        self.name_resolver
+    /// Get the name of the register at `addr`.
+    ///
+    /// The name is looked up in the [`database`](#structfield.database) first
+    /// and in the [`name_resolver`](#structfield.name_resolver) second.
+    /// Whitespace and trailing commas returned by the resolver are stripped.
+    pub fn get_reg_name(&self, addr: usize) -> Option<&str> {
+        self.database
             .as_ref()
-            .and_then(|r| r(addr as u64))
-            .copied()
+            .and_then(|db| db.name(addr))
+            .or_else(|| {
+                match &self.name_resolver {
+                    Some(resolver) => resolver(addr as u64),
+                    // moved away while the end of test checks run
+                    None => crate::matchers::resolve_verifying(addr),
+                }
+                .map(|name| name.trim().trim_end_matches(','))
+            })
+    }
+
+    /// Render a [`RegisterAccess`] in a human-readable way, using the register
+    /// name and bitfields if they are known. Ends with the source location of
+    /// the access if it was recorded, see [`RegisterAccess::location`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use regmock_rs::utils::{Regmock, RegisterAccess, RegisterAccessType};
+    ///
+    /// let mock = Regmock::default();
+    /// let access = RegisterAccess::new(RegisterAccessType::WRITE, 0x8204, 4, 0x0, 0x3);
+    /// assert_eq!(
+    ///     mock.format_access(&access),
+    ///     "WRITE 0x00008204 len:4 0x00000000 -> 0x00000003"
+    /// );
+    /// ```
+    pub fn format_access(&self, access: &RegisterAccess) -> String {
+        let mut text = match &access.ty {
+            Some(ty) => format!("{ty:?}"),
+            None => "ACCESS".to_owned(),
+        };
+        if let Some(addr) = access.addr {
+            match self.get_reg_name(addr) {
+                Some(name) => text += &format!(" {name} (0x{addr:08X})"),
+                None => text += &format!(" 0x{addr:08X}"),
+            }
+        }
+        if let Some(len) = access.len {
+            text += &format!(" len:{len}");
+        }
+        match (access.before, access.after) {
+            (Some(before), Some(after)) => text += &format!(" 0x{before:08X} -> 0x{after:08X}"),
+            (Some(before), None) => text += &format!(" 0x{before:08X} -> ?"),
+            (None, Some(after)) => text += &format!(" -> 0x{after:08X}"),
+            (None, None) => {}
+        }
+        let register = access
+            .addr
+            .and_then(|addr| self.database.as_ref()?.get(addr));
+        if let (Some(register), Some(after)) = (register, access.after) {
+            if !register.fields.is_empty() {
+                text += &format!(" [{}]", register.describe_value(after));
+            }
+        }
+        if let Some(location) = access.location.as_ref().and_then(|l| l.resolve()) {
+            text += &format!(" at {location}");
+        }
+        text
+    }
+
+    /// Render the whole [`log`](#structfield.log) in a human-readable way,
+    /// one line per log entry. See [`Regmock::format_access`].
+    pub fn format_log(&self) -> String {
+        self.log
+            .log
+            .iter()
+            .map(|(access, count)| match count {
+                1 => self.format_access(access),
+                count => format!("{} (x{count})", self.format_access(access)),
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
     }
 }
 
@@ -584,19 +2244,45 @@ impl Regmock {
     /// ```
     /// To register the function with the PAC library. Consult the documentation
     /// of your specific PAC for more information.
+    #[track_caller]
     pub fn read_volatile(&mut self, addr: usize, len: usize) -> u64 {
-        let before = self.get_reg_value(addr);
-        let after = self.exec_read_fn(addr, before);
+        let lane = self.lane(addr, len);
+        let before = self.peek_reg_value(lane.base);
+        let allowed = match self.access_violation(&RegisterAccessType::READ, addr, len, &lane) {
+            Some(kind) => {
+                let value = lane.extract(before);
+                self.report_violation(
+                    kind,
+                    RegisterAccess::new(RegisterAccessType::READ, addr, len, value, value),
+                )
+            }
+            None => true,
+        };
+        let after = if allowed {
+            self.get_reg_value(lane.base);
+            let after = self.exec_read_fn(&lane, before);
+            self.apply_read_semantics(lane.base, lane.mask);
+            lane.extract(after)
+        } else {
+            0
+        };
 
-        if self.log_enabled {
-            self.log.push_log_entry(RegisterAccess::new(
-                RegisterAccessType::READ,
-                addr,
-                len,
-                before,
-                after,
-            ));
+        let mut access = RegisterAccess::new(
+            RegisterAccessType::READ,
+            addr,
+            len,
+            lane.extract(before),
+            after,
+        );
+        if let Some(value) = self.replay_access(&access) {
+            let register = self.peek_reg_value(lane.base);
+            self.register_mocks
+                .insert(lane.base, lane.insert(register, value));
+            access.after = Some(lane.extract(lane.insert(register, value)));
         }
+        let after = access.after.unwrap_or(after);
+        self.log_access(access);
+        self.finish_access(self.read_cost);
         after
     }
 
@@ -612,39 +2298,180 @@ pub fn read_volatile(&mut self, addr: usize, len: usize) -> u64 {
     /// ```
     /// To register the function with the PAC library. Consult the documentation
     /// of your specific PAC for more information.
+    #[track_caller]
     pub fn write_volatile(&mut self, addr: usize, len: usize, val: u64) {
-        let before = self.get_reg_value(addr);
-        let after = self.exec_write_fn(addr, before, val);
+        let lane = self.lane(addr, len);
+        let before = self.peek_reg_value(lane.base);
+        let allowed = match self.access_violation(&RegisterAccessType::WRITE, addr, len, &lane) {
+            Some(kind) => self.report_violation(
+                kind,
+                RegisterAccess::new(
+                    RegisterAccessType::WRITE,
+                    addr,
+                    len,
+                    lane.extract(before),
+                    val,
+                ),
+            ),
+            None => true,
+        };
+        let after = if allowed {
+            let val = lane.insert(before, val);
+            let val = self.apply_write_semantics(lane.base, before, val, lane.mask);
+            self.exec_write_fn(RegisterAccessType::WRITE, &lane, before, val) & lane.register_mask()
+        } else {
+            before
+        };
+        if self.callback_enabled {
+            self.written_since_reset.insert(lane.base);
+        }
 
-        if self.log_enabled {
-            self.log.push_log_entry(RegisterAccess::new(
-                RegisterAccessType::WRITE,
-                addr,
-                len,
-                before,
-                after,
-            ));
+        let access = RegisterAccess::new(
+            RegisterAccessType::WRITE,
+            addr,
+            len,
+            lane.extract(before),
+            lane.extract(after),
+        );
+        // the trace holds the written value, not the register value after
+        // the write semantics were applied
+        self.replay_access(&RegisterAccess {
+            after: Some(lane.extract(lane.insert(before, val))),
+            ..access.clone()
+        });
+        self.log_access(access);
+        if allowed {
+            self.register_mocks.insert(lane.base, after);
         }
-        self.register_mocks.insert(addr, after);
+        self.finish_access(self.write_cost);
     }
 
+    /// Perform an `LDMST` (load-modify-store) on a mocked register.
+    ///
+    /// `value` holds the data in the lower and the mask in the upper 32 bits,
+    /// like the 64 bit register operand of the instruction. Only the bits set
+    /// in the mask are modified: `(before & !mask) | (data & mask)`.
+    ///
+    /// Callbacks in [`ldmst_fn`](#structfield.ldmst_fn) get passed the mask
+    /// and the data. The access is logged as [`RegisterAccessType::LDMST`]
+    /// with the mask in [`RegisterAccess::mask`].
     #[cfg(feature = "aurix")]
-    /// Basic impl of the LMST tracing interace for the Aurix chip.
-    /// TODO: maybe add a dedicated field for callbacks for lmst register
-    /// accesses.
-    pub fn load_modify_store(&mut self, addr: usize, len: usize, val: u64) {
-        let before = self.get_reg_value(addr);
-        let after = self.exec_write_fn(addr, before, val);
-
-        if !self.log_enabled {
-            self.log.push_log_entry(RegisterAccess::new(
-                RegisterAccessType::WRITE,
-                addr,
-                len,
-                before,
-                after,
-            ));
+    #[track_caller]
+    pub fn load_modify_store(&mut self, addr: usize, value: u64) {
+        let (data, mask) = (value & 0xFFFF_FFFF, value >> 32);
+        self.atomic_access(RegisterAccessType::LDMST, addr, data, Some(mask), |old| {
+            Some((old & !mask) | (data & mask))
+        });
+    }
+
+    /// Perform a `SWAP.W` on a mocked register: `data` is written to the
+    /// register and its previous value is returned.
+    ///
+    /// Logged as a single [`RegisterAccessType::SWAP`] access. Write callbacks
+    /// and models are called once with the access type in [`AccessContext::ty`].
+    #[cfg(feature = "aurix")]
+    #[track_caller]
+    pub fn swap(&mut self, addr: usize, data: u64) -> u64 {
+        self.atomic_access(RegisterAccessType::SWAP, addr, data, None, |_| Some(data))
+    }
+
+    /// Perform a `SWAPMSK.W` on a mocked register: the bits of the data that
+    /// are set in the mask are written to the register and its previous value
+    /// is returned.
+    ///
+    /// `value` holds the data in the lower and the mask in the upper 32 bits.
+    /// Logged as a single [`RegisterAccessType::SWAPMSK`] access with the
+    /// mask in [`RegisterAccess::mask`].
+    #[cfg(feature = "aurix")]
+    #[track_caller]
+    pub fn swap_masked(&mut self, addr: usize, value: u64) -> u64 {
+        let (data, mask) = (value & 0xFFFF_FFFF, value >> 32);
+        self.atomic_access(RegisterAccessType::SWAPMSK, addr, data, Some(mask), |old| {
+            Some((old & !mask) | (data & mask))
+        })
+    }
+
+    /// Perform a `CMPSWAP.W` on a mocked register: the data is written to the
+    /// register if its value equals the compare value. The previous value of
+    /// the register is returned in any case.
+    ///
+    /// `value` holds the data in the lower and the compare value in the upper
+    /// 32 bits. Logged as a single [`RegisterAccessType::CMPSWAP`] access,
+    /// a failed comparison shows up as an access that does not change the
+    /// value. Nothing is written then, so write callbacks and models are not
+    /// called and the write semantics of the register are not applied.
+    #[cfg(feature = "aurix")]
+    #[track_caller]
+    pub fn compare_and_swap(&mut self, addr: usize, value: u64) -> u64 {
+        let (data, compare) = (value & 0xFFFF_FFFF, value >> 32);
+        self.atomic_access(RegisterAccessType::CMPSWAP, addr, data, None, |old| {
+            (old == compare).then_some(data)
+        })
+    }
+
+    /// Perform an atomic read-modify-write of a 32 bit register.
+    ///
+    /// `modify` computes the new value of the accessed bits from their
+    /// previous value, which is returned, or `None` if nothing is written.
+    /// Bits outside of `mask` are not modified. Accesses other than `LDMST`
+    /// return the previous value and are checked for read violations too.
+    #[cfg(feature = "aurix")]
+    #[track_caller]
+    fn atomic_access(
+        &mut self,
+        ty: RegisterAccessType,
+        addr: usize,
+        data: u64,
+        mask: Option<u64>,
+        modify: impl FnOnce(u64) -> Option<u64>,
+    ) -> u64 {
+        let len = 4;
+        let lane = self.lane(addr, len);
+        let before = self.peek_reg_value(lane.base);
+        let old = lane.extract(before);
+        let new = modify(old);
+        let mut access = RegisterAccess::new(ty.clone(), addr, len, old, new.unwrap_or(old));
+        access.mask = mask;
+        let violation = if ty == RegisterAccessType::LDMST {
+            None
+        } else {
+            self.access_violation(&RegisterAccessType::READ, addr, len, &lane)
+        }
+        .or_else(|| self.access_violation(&RegisterAccessType::WRITE, addr, len, &lane));
+        let allowed = match violation {
+            Some(kind) => self.report_violation(kind, access.clone()),
+            None => true,
+        };
+        let written = allowed && new.is_some();
+        let after = if written {
+            let merged = lane.insert(before, access.after.unwrap_or(old));
+            let modified = lane.insert(0, mask.unwrap_or(u64::MAX));
+            let merged = self.apply_write_semantics(lane.base, before, merged, modified);
+            let after = match self.ldmst_fn.get_mut(&lane.base) {
+                Some(cb) if self.callback_enabled && ty == RegisterAccessType::LDMST => cb(
+                    &mut self.register_mocks,
+                    before,
+                    mask.unwrap_or(u64::MAX),
+                    data,
+                ),
+                _ => self.exec_write_fn(ty, &lane, before, merged),
+            };
+            after & lane.register_mask()
+        } else {
+            before
+        };
+        if written && self.callback_enabled {
+            self.written_since_reset.insert(lane.base);
+        }
+
+        // replayed with the value computed by the instruction, see `write_volatile`
+        self.replay_access(&access);
+        access.after = Some(lane.extract(after));
+        self.log_access(access);
+        if written {
+            self.register_mocks.insert(lane.base, after);
         }
-        self.register_mocks.insert(addr, after);
+        self.finish_access(self.write_cost);
+        old
     }
 }
@@ -0,0 +1,284 @@
+//! Export of the access log as a Value Change Dump (VCD) file.
+//!
+//! The exported file has one signal per accessed register that holds the value
+//! of the register after each access, and optionally one signal per bitfield
+//! of registers with metadata. It can be opened in waveform viewers like
+//! GTKWave to inspect the timing of driver code.
+use std::collections::BTreeMap;
+use std::io::{self, Write};
+use std::time::{Duration, SystemTime};
+
+use crate::database::FieldInfo;
+use crate::utils::{RegisterAccess, Regmock, RegmockLog};
+
+/// Time axis of an exported VCD file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Timebase {
+    /// [`Virtual`](Timebase::Virtual) time if it advances during the log,
+    /// [`Sequence`](Timebase::Sequence) numbers otherwise, so the accesses of
+    /// tests that don't use virtual time are not all written at time 0.
+    #[default]
+    Auto,
+    /// Virtual time of the accesses in nanoseconds, see [`Regmock::now`].
+    Virtual,
+    /// Sequence numbers of the accesses, one time step per access. Useful if
+    /// the virtual time does not advance.
+    Sequence,
+    /// Wall-clock time of the accesses in nanoseconds since the first access.
+    WallClock,
+}
+
+/// Options of the VCD export.
+#[derive(Debug, Clone, Default)]
+pub struct VcdOptions {
+    /// Time axis of the file.
+    pub timebase: Timebase,
+    /// Add a signal per bitfield for registers with metadata, see
+    /// [`Regmock::database`].
+    pub bitfields: bool,
+}
+
+/// A signal in the VCD file.
+struct Signal {
+    id: String,
+    width: usize,
+}
+
+impl RegmockLog {
+    /// Write the log as VCD to `out`, with signals named by register address.
+    ///
+    /// Use [`Regmock::write_vcd`] to name the signals after the registers.
+    pub fn write_vcd(&self, out: impl Write, options: &VcdOptions) -> io::Result<()> {
+        write_vcd(self, &Regmock::default(), out, options)
+    }
+}
+
+impl Regmock {
+    /// Write the [`log`](#structfield.log) as VCD to `out`.
+    ///
+    /// Signals are named after the registers using the
+    /// [`database`](#structfield.database) or the name resolver. Accesses to
+    /// some bytes of a register change the signal of the whole register.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::Duration;
+    /// use regmock_rs::utils::Regmock;
+    /// use regmock_rs::vcd::VcdOptions;
+    ///
+    /// let mut mock = Regmock::default();
+    /// mock.write_volatile(0x100, 4, 0x1);
+    /// mock.advance_time(Duration::from_micros(1));
+    /// mock.write_volatile(0x100, 4, 0x2);
+    ///
+    /// let mut vcd = Vec::new();
+    /// mock.write_vcd(&mut vcd, &VcdOptions::default()).unwrap();
+    /// let vcd = String::from_utf8(vcd).unwrap();
+    /// assert!(vcd.contains("$var wire 32 ! reg_0x00000100 $end"));
+    /// assert!(vcd.contains("#1000\nb10 !"));
+    /// ```
+    pub fn write_vcd(&self, out: impl Write, options: &VcdOptions) -> io::Result<()> {
+        write_vcd(&self.log, self, out, options)
+    }
+}
+
+/// Write `log` as VCD, with the registers of the accesses and their names
+/// and bitfields looked up in `mock`.
+fn write_vcd(
+    log: &RegmockLog,
+    mock: &Regmock,
+    mut out: impl Write,
+    options: &VcdOptions,
+) -> io::Result<()> {
+    let lanes = mock.lanes_of(log);
+    let register = |base: usize| mock.database.as_ref()?.get(base);
+
+    // width in bits and value before the first access of each register,
+    // made of the bytes before the first access to each of them
+    let mut registers = BTreeMap::new();
+    for ((access, _), lane) in log.log.iter().zip(&lanes) {
+        let Some(lane) = lane else {
+            continue;
+        };
+        let width = register(lane.base).map_or(lane.width * 8, |r| r.size);
+        let (_, initial, known) = registers.entry(lane.base).or_insert((width, 0, 0));
+        if let Some(before) = access.before {
+            let unknown = lane.mask & !*known;
+            *initial |= lane.insert(0, before) & unknown;
+            *known |= unknown;
+        }
+    }
+
+    writeln!(
+        out,
+        "$version regmock-rs {} $end",
+        env!("CARGO_PKG_VERSION")
+    )?;
+    writeln!(out, "$timescale 1ns $end")?;
+    writeln!(out, "$scope module regmock $end")?;
+    let mut signals = BTreeMap::new();
+    let mut fields = BTreeMap::new();
+    let mut ids = (0..).map(identifier);
+    for (&base, &(width, _, _)) in &registers {
+        let reference = mock
+            .get_reg_name(base)
+            .map(sanitize)
+            .unwrap_or_else(|| format!("reg_0x{base:08X}"));
+        let signal = Signal {
+            id: ids.next().unwrap(),
+            width,
+        };
+        writeln!(
+            out,
+            "$var wire {} {} {reference} $end",
+            signal.width, signal.id
+        )?;
+        signals.insert(base, signal);
+
+        let register = register(base).filter(|_| options.bitfields);
+        if let Some(register) = register.filter(|r| !r.fields.is_empty()) {
+            writeln!(out, "$scope module {reference} $end")?;
+            for field in &register.fields {
+                let signal = Signal {
+                    id: ids.next().unwrap(),
+                    width: field.bit_width,
+                };
+                writeln!(
+                    out,
+                    "$var wire {} {} {} $end",
+                    signal.width,
+                    signal.id,
+                    sanitize(&field.name)
+                )?;
+                fields
+                    .entry(base)
+                    .or_insert_with(Vec::new)
+                    .push((field, signal));
+            }
+            writeln!(out, "$upscope $end")?;
+        }
+    }
+    writeln!(out, "$upscope $end")?;
+    writeln!(out, "$enddefinitions $end")?;
+
+    // initial values are the values before the first access
+    writeln!(out, "#0")?;
+    writeln!(out, "$dumpvars")?;
+    let mut values = BTreeMap::new();
+    for (&base, &(_, initial, known)) in &registers {
+        let value = (known != 0).then_some(initial);
+        write_register(&mut out, &signals[&base], fields.get(&base), value)?;
+        values.insert(base, initial);
+    }
+    writeln!(out, "$end")?;
+
+    let timebase = match options.timebase {
+        Timebase::Auto => {
+            let mut times = log
+                .log
+                .iter()
+                .flat_map(|(access, _)| [access.time, access.last_time])
+                .flatten();
+            let first = times.next();
+            if times.any(|time| Some(time) != first) {
+                Timebase::Virtual
+            } else {
+                Timebase::Sequence
+            }
+        }
+        timebase => timebase,
+    };
+    let start = log.log.iter().find_map(|(access, _)| access.wall_time);
+    let mut now = 0;
+    for ((access, _), lane) in log.log.iter().zip(&lanes) {
+        let Some(lane) = lane else {
+            continue;
+        };
+        let time = timestamp(access, timebase, start).unwrap_or(now);
+        if time > now {
+            now = time;
+            writeln!(out, "#{now}")?;
+        }
+        let value = values.get_mut(&lane.base).unwrap();
+        if let Some(after) = access.after {
+            *value = lane.insert(*value, after);
+        }
+        write_register(
+            &mut out,
+            &signals[&lane.base],
+            fields.get(&lane.base),
+            Some(*value),
+        )?;
+    }
+    Ok(())
+}
+
+/// Write the value of a register and of its bitfields.
+fn write_register(
+    out: &mut impl Write,
+    signal: &Signal,
+    fields: Option<&Vec<(&FieldInfo, Signal)>>,
+    value: Option<u64>,
+) -> io::Result<()> {
+    write_value(out, signal, value)?;
+    for (field, signal) in fields.into_iter().flatten() {
+        write_value(out, signal, value.map(|v| field.extract(v)))?;
+    }
+    Ok(())
+}
+
+/// Get the time of `access` on the time axis of the file.
+fn timestamp(
+    access: &RegisterAccess,
+    timebase: Timebase,
+    start: Option<SystemTime>,
+) -> Option<u64> {
+    let time = match timebase {
+        Timebase::Auto | Timebase::Virtual => access.time?,
+        Timebase::Sequence => return access.seq,
+        Timebase::WallClock => access
+            .wall_time?
+            .duration_since(start?)
+            .unwrap_or(Duration::ZERO),
+    };
+    Some(time.as_nanos() as u64)
+}
+
+fn write_value(out: &mut impl Write, signal: &Signal, value: Option<u64>) -> io::Result<()> {
+    match (value, signal.width) {
+        (Some(value), 1) => writeln!(out, "{}{}", value & 0x1, signal.id),
+        (None, 1) => writeln!(out, "x{}", signal.id),
+        (Some(value), _) => writeln!(out, "b{value:b} {}", signal.id),
+        (None, _) => writeln!(out, "bx {}", signal.id),
+    }
+}
+
+/// Get the identifier code of the `index`th signal, made of the printable
+/// ASCII characters `!` to `~`.
+fn identifier(mut index: usize) -> String {
+    let mut id = String::new();
+    loop {
+        id.push((b'!' + (index % 94) as u8) as char);
+        index /= 94;
+        if index == 0 {
+            return id;
+        }
+        index -= 1;
+    }
+}
+
+/// Turn a register name like `TIMER.timercluster()[0].ctrlstat()` into a
+/// VCD reference like `TIMER_timercluster_0_ctrlstat`.
+fn sanitize(name: &str) -> String {
+    let mut reference = String::new();
+    for c in name.replace("()", "").replace("r#", "").chars() {
+        match c {
+            c if c.is_ascii_alphanumeric() => reference.push(c),
+            ']' => {}
+            _ if reference.ends_with('_') => {}
+            _ => reference.push('_'),
+        }
+    }
+    reference.trim_end_matches('_').to_owned()
+}
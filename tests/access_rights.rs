@@ -0,0 +1,134 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::database::RegisterDatabase;
+use regmock_rs::utils::{AccessPolicy, RegisterAccessType, Regmock, ViolationKind};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+static ACCESS_SVD: &str = r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>ro</name>
+                    <addressOffset>0x0</addressOffset>
+                    <access>read-only</access>
+                </register>
+                <register>
+                    <name>wo</name>
+                    <addressOffset>0x4</addressOffset>
+                    <access>write-only</access>
+                </register>
+                <register>
+                    <name>once</name>
+                    <addressOffset>0x8</addressOffset>
+                    <access>read-writeOnce</access>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+
+fn strict_mock(policy: AccessPolicy) -> Regmock {
+    Regmock::strict(RegisterDatabase::from_svd_str(ACCESS_SVD).unwrap(), policy)
+}
+
+#[test]
+fn permissive_by_default() {
+    let mut mock = Regmock::from_svd_str(ACCESS_SVD).unwrap();
+    mock.write_volatile(0x100, 4, 0x1);
+    assert_eq!(mock.read_volatile(0x100, 4), 0x1);
+    assert!(mock.log.violations.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "WriteToReadOnly")]
+fn panic_on_write_to_read_only() {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    let mock = Regmock::strict(database, AccessPolicy::Panic);
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        #[allow(unused_imports)]
+        use pac::tracing::insanely_unsafe;
+        GPIO.r#in().write_read_only(gpio::In::new(0x1));
+    }
+}
+
+#[test]
+fn silent_accesses_are_allowed() {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    let mock = Regmock::strict(database, AccessPolicy::Panic);
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    regmock_rs::silent(|| unsafe {
+        #[allow(unused_imports)]
+        use pac::tracing::insanely_unsafe;
+        GPIO.r#in().write_read_only(gpio::In::new(0x5));
+    });
+    assert_eq!(unsafe { GPIO.r#in().read().get_raw() }, 0x5);
+    assert!(regmock_rs::logs().violations.is_empty());
+}
+
+#[test]
+fn record_violations() {
+    let mut mock = strict_mock(AccessPolicy::Record);
+    mock.write_volatile(0x100, 4, 0x1);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x0);
+    mock.write_volatile(0x108, 4, 0x2);
+    mock.write_volatile(0x108, 4, 0x3);
+
+    // accesses are performed anyway
+    assert_eq!(mock.read_volatile(0x100, 4), 0x1);
+    assert_eq!(mock.read_volatile(0x108, 4), 0x3);
+
+    let kinds: Vec<_> = mock
+        .log
+        .violations
+        .iter()
+        .map(|v| (v.kind.clone(), v.access.addr.unwrap(), v.log_index))
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            (ViolationKind::WriteToReadOnly, 0x100, 0),
+            (ViolationKind::ReadFromWriteOnly, 0x104, 1),
+            (ViolationKind::RepeatedWriteOnce, 0x108, 3),
+        ]
+    );
+    assert_eq!(
+        mock.log.violations[0].access.ty,
+        Some(RegisterAccessType::WRITE)
+    );
+    assert_eq!(mock.log.violations[0].access.after, Some(0x1));
+}
+
+#[test]
+fn drop_violating_accesses() {
+    let mut mock = strict_mock(AccessPolicy::Drop);
+    mock.write_volatile(0x100, 4, 0x1);
+    assert_eq!(mock.read_volatile(0x100, 4), 0x0);
+
+    mock.register_mocks.insert(0x104, 0xAB);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x0);
+
+    mock.write_volatile(0x108, 4, 0x2);
+    mock.write_volatile(0x108, 4, 0x3);
+    assert_eq!(mock.read_volatile(0x108, 4), 0x2);
+    assert_eq!(mock.log.violations.len(), 3);
+
+    // write-once registers can be written again after a reset
+    mock.reset();
+    mock.write_volatile(0x108, 4, 0x4);
+    assert_eq!(mock.read_volatile(0x108, 4), 0x4);
+    assert_eq!(mock.log.violations.len(), 3);
+}
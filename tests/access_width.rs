@@ -0,0 +1,124 @@
+use regmock_rs::database::RegisterDatabase;
+use regmock_rs::utils::{AccessPolicy, RegisterAccess, RegisterAccessType, Regmock, ViolationKind};
+
+static WIDTH_SVD: &str = r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>word</name>
+                    <addressOffset>0x0</addressOffset>
+                </register>
+                <register>
+                    <name>half</name>
+                    <addressOffset>0x8</addressOffset>
+                    <size>16</size>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+
+#[test]
+fn byte_lanes_of_database_register() {
+    let mut mock = Regmock::from_svd_str(WIDTH_SVD).unwrap();
+
+    mock.write_volatile(0x100, 4, 0x1122_3344);
+    mock.write_volatile(0x102, 1, 0xAB);
+    mock.write_volatile(0x101, 1, 0x1CD);
+    assert_eq!(mock.register_mocks[&0x100], 0x11AB_CD44);
+    assert_eq!(mock.register_mocks.len(), 1);
+
+    assert_eq!(mock.read_volatile(0x103, 1), 0x11);
+    assert_eq!(mock.read_volatile(0x102, 2), 0x11AB);
+    assert_eq!(mock.read_volatile(0x100, 4), 0x11AB_CD44);
+
+    // log entries describe the accessed bytes only
+    assert_eq!(
+        mock.log.log[1].0,
+        RegisterAccess::new(RegisterAccessType::WRITE, 0x102, 1, 0x22, 0xAB)
+    );
+    assert_eq!(
+        mock.log.log[3].0,
+        RegisterAccess::new(RegisterAccessType::READ, 0x103, 1, 0x11, 0x11)
+    );
+}
+
+#[test]
+fn byte_lanes_without_metadata() {
+    let mut mock = Regmock::default();
+
+    // the first access defines the width of the register until a wider one
+    mock.write_volatile(0x100, 4, 0xAABB_CCDD);
+    mock.write_volatile(0x103, 1, 0x00);
+    assert_eq!(mock.register_mocks[&0x100], 0x00BB_CCDD);
+    assert_eq!(mock.read_volatile(0x101, 1), 0xCC);
+    assert!(!mock.register_mocks.contains_key(&0x103));
+
+    // accesses beyond the register define a new one
+    mock.write_volatile(0x104, 2, 0x1234);
+    assert_eq!(mock.register_mocks[&0x104], 0x1234);
+}
+
+#[test]
+fn wider_access_widens_register_without_metadata() {
+    let mut mock = Regmock::default();
+    mock.access_policy = AccessPolicy::Record;
+
+    // a byte access first does not fix the width of the register
+    mock.write_volatile(0x100, 1, 0x78);
+    mock.write_volatile(0x100, 4, 0x1234_5678);
+    assert_eq!(mock.read_volatile(0x100, 4), 0x1234_5678);
+    assert_eq!(mock.read_volatile(0x103, 1), 0x12);
+
+    // registers inferred from byte accesses are merged with their values
+    mock.write_volatile(0x110, 1, 0x11);
+    mock.write_volatile(0x111, 1, 0x22);
+    mock.write_volatile(0x113, 1, 0x44);
+    assert_eq!(mock.read_volatile(0x110, 4), 0x4400_2211);
+    assert_eq!(mock.register_mocks[&0x110], 0x4400_2211);
+    assert!(!mock.register_mocks.contains_key(&0x111));
+    assert!(mock.log.violations.is_empty());
+}
+
+#[test]
+fn values_are_masked_to_register_width() {
+    let mut mock = Regmock::from_svd_str(WIDTH_SVD).unwrap();
+    mock.write_volatile(0x108, 4, 0xFFFF_FFFF);
+    assert_eq!(mock.register_mocks[&0x108], 0xFFFF);
+
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x200, 1, 0x1FF);
+    assert_eq!(mock.read_volatile(0x200, 1), 0xFF);
+}
+
+#[test]
+fn record_misaligned_and_oversized_accesses() {
+    let database = RegisterDatabase::from_svd_str(WIDTH_SVD).unwrap();
+    let mut mock = Regmock::strict(database, AccessPolicy::Record);
+
+    mock.write_volatile(0x101, 2, 0x1);
+    let _ = mock.read_volatile(0x108, 4);
+    mock.write_volatile(0x102, 2, 0x1);
+
+    let kinds: Vec<_> = mock.log.violations.iter().map(|v| v.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ViolationKind::Misaligned,
+            ViolationKind::Oversized { register_size: 2 }
+        ]
+    );
+}
+
+#[test]
+#[should_panic(expected = "Misaligned")]
+fn panic_on_misaligned_access() {
+    let mut mock = Regmock::default();
+    mock.access_policy = AccessPolicy::Panic;
+    mock.write_volatile(0x102, 4, 0x1);
+}
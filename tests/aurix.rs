@@ -0,0 +1,231 @@
+#![cfg(feature = "aurix")]
+use std::sync::{Arc, Mutex};
+
+use pac::{spi, RegisterValue, SPI};
+use regmock_rs::database::RegisterDatabase;
+use regmock_rs::utils::{
+    AccessPolicy, Callback, RegisterAccess, RegisterAccessType, Regmock, ViolationKind,
+};
+use regmock_rs::{given, require_reg};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+static ATOMIC_SVD: &str = r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>flags</name>
+                    <addressOffset>0x0</addressOffset>
+                    <fields>
+                        <field><name>w1c</name><bitRange>[3:0]</bitRange><modifiedWriteValues>oneToClear</modifiedWriteValues></field>
+                    </fields>
+                </register>
+                <register>
+                    <name>wo</name>
+                    <addressOffset>0x4</addressOffset>
+                    <access>write-only</access>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+
+/// Operand of an `LDMST` with the data in the lower and the mask in the upper word.
+fn operand(mask: u32, data: u32) -> u64 {
+    (mask as u64) << 32 | data as u64
+}
+
+#[test]
+fn ldmst_modifies_masked_bits() {
+    let mut mock = Regmock::default();
+    mock.register_mocks.insert(0x100, 0xAAAA_AAAA);
+    mock.load_modify_store(0x100, operand(0x0000_FF00, 0x1234_5678));
+    assert_eq!(mock.register_mocks[&0x100], 0xAAAA_56AA);
+
+    let mut expected = RegisterAccess::new(
+        RegisterAccessType::LDMST,
+        0x100,
+        4,
+        0xAAAA_AAAA,
+        0xAAAA_56AA,
+    );
+    expected.mask = Some(0x0000_FF00);
+    assert_eq!(mock.log.log.len(), 1);
+    assert_eq!(mock.log.log[0].0, expected);
+    assert_eq!(mock.log.log[0].0.mask, Some(0x0000_FF00));
+}
+
+#[test]
+fn ldmst_is_not_logged_when_logging_is_disabled() {
+    let mut mock = Regmock::default();
+    mock.log_enabled = false;
+    mock.load_modify_store(0x100, operand(0x1, 0x1));
+    assert!(mock.log.log.is_empty());
+    assert_eq!(mock.register_mocks[&0x100], 0x1);
+}
+
+#[test]
+fn ldmst_callbacks_get_mask_and_data() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let mut mock = Regmock::default();
+    mock.ldmst_fn.insert(
+        0x100,
+        Box::new(move |_, before, mask, data| {
+            recorded.lock().unwrap().push((before, mask, data));
+            before ^ mask
+        }),
+    );
+    mock.write_fn.insert(0x104, Box::new(|_, _, val| val << 4));
+
+    mock.register_mocks.insert(0x100, 0x0F);
+    mock.load_modify_store(0x100, operand(0x3, 0x2));
+    assert_eq!(*seen.lock().unwrap(), vec![(0x0F, 0x3, 0x2)]);
+    assert_eq!(mock.register_mocks[&0x100], 0x0C);
+
+    // without an `LDMST` callback, the merged value is passed to the write callbacks
+    mock.load_modify_store(0x104, operand(0xF, 0x5));
+    assert_eq!(mock.register_mocks[&0x104], 0x50);
+}
+
+#[test]
+fn write_matchers_consider_ldmst() {
+    init_mock(None);
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+    regmock_rs::ldmst_fn(SPI.ctrl().addr(), operand(0x2, 0x2));
+
+    given!(
+        full_log,
+        require_reg!(SPI.ctrl(), values_written_are([0x1u64, 0x3]))
+    );
+    assert_eq!(unsafe { SPI.ctrl().read().get_raw() }, 0x3);
+}
+
+#[test]
+fn swap_returns_previous_value() {
+    let mut mock = Regmock::default();
+    mock.register_mocks.insert(0x100, 0x1111);
+    assert_eq!(mock.swap(0x100, 0x2222), 0x1111);
+    assert_eq!(mock.register_mocks[&0x100], 0x2222);
+
+    assert_eq!(mock.swap_masked(0x100, operand(0x00FF, 0x3333)), 0x2222);
+    assert_eq!(mock.register_mocks[&0x100], 0x2233);
+
+    let types: Vec<_> = mock.log.iter().map(|a| a.ty.clone().unwrap()).collect();
+    assert_eq!(
+        types,
+        vec![RegisterAccessType::SWAP, RegisterAccessType::SWAPMSK]
+    );
+    assert_eq!(mock.log.log[1].0.mask, Some(0x00FF));
+    assert_eq!(mock.log.log[1].0.before, Some(0x2222));
+    assert_eq!(mock.log.log[1].0.after, Some(0x2233));
+}
+
+#[test]
+fn compare_and_swap() {
+    let mut mock = Regmock::default();
+    mock.register_mocks.insert(0x100, 0x5);
+
+    // comparison fails, the register is not modified
+    assert_eq!(mock.compare_and_swap(0x100, operand(0x4, 0x9)), 0x5);
+    assert_eq!(mock.register_mocks[&0x100], 0x5);
+    // comparison succeeds
+    assert_eq!(mock.compare_and_swap(0x100, operand(0x5, 0x9)), 0x5);
+    assert_eq!(mock.register_mocks[&0x100], 0x9);
+
+    assert_eq!(mock.log.len_full(), 2);
+    assert_eq!(
+        mock.log.log[0].0,
+        RegisterAccess::new(RegisterAccessType::CMPSWAP, 0x100, 4, 0x5, 0x5)
+    );
+    assert_eq!(
+        mock.log.log[1].0,
+        RegisterAccess::new(RegisterAccessType::CMPSWAP, 0x100, 4, 0x5, 0x9)
+    );
+}
+
+#[test]
+fn failed_compare_and_swap_does_not_write() {
+    let mut mock = Regmock::from_svd_str(ATOMIC_SVD).unwrap();
+    mock.register_mocks.insert(0x100, 0x5);
+
+    assert_eq!(mock.compare_and_swap(0x100, operand(0x4, 0x0)), 0x5);
+    // writing back the value would have cleared the flags
+    assert_eq!(mock.register_mocks[&0x100], 0x5);
+    assert!(!mock.log.log[0].0.is_write());
+}
+
+#[test]
+fn swap_on_write_only_register_is_a_read_violation() {
+    let database = RegisterDatabase::from_svd_str(ATOMIC_SVD).unwrap();
+    let mut mock = Regmock::strict(database, AccessPolicy::Record);
+    let _ = mock.swap(0x104, 0x1);
+    let _ = mock.compare_and_swap(0x104, operand(0x0, 0x2));
+    // `LDMST` does not return the register value
+    mock.load_modify_store(0x104, operand(0x0, 0x3));
+
+    let kinds: Vec<_> = mock.log.violations.iter().map(|v| v.kind.clone()).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            ViolationKind::ReadFromWriteOnly,
+            ViolationKind::ReadFromWriteOnly
+        ]
+    );
+}
+
+#[test]
+fn callbacks_see_atomic_access() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let mut mock = Regmock::default();
+    mock.read_callbacks
+        .insert(0x100, Callback::new(|_| panic!("atomic access is no read")));
+    mock.write_callbacks.insert(
+        0x100,
+        Callback::new(move |ctx| {
+            recorded
+                .lock()
+                .unwrap()
+                .push((ctx.ty.clone(), ctx.before, ctx.value));
+            ctx.value
+        }),
+    );
+    mock.register_mocks.insert(0x100, 0x1);
+
+    assert_eq!(mock.swap(0x100, 0x2), 0x1);
+    assert_eq!(mock.compare_and_swap(0x100, operand(0x2, 0x3)), 0x2);
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            (RegisterAccessType::SWAP, 0x1, 0x2),
+            (RegisterAccessType::CMPSWAP, 0x2, 0x3)
+        ]
+    );
+}
+
+#[test]
+fn atomic_accesses_through_free_functions() {
+    init_mock(None);
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+    assert_eq!(regmock_rs::swap_fn(SPI.ctrl().addr(), 0x2), 0x1);
+    assert_eq!(
+        regmock_rs::swapmsk_fn(SPI.ctrl().addr(), operand(0x1, 0x1)),
+        0x2
+    );
+    assert_eq!(
+        regmock_rs::cmpswap_fn(SPI.ctrl().addr(), operand(0x3, 0x0)),
+        0x3
+    );
+    given!(
+        full_log,
+        require_reg!(SPI.ctrl(), values_written_are([0x1u64, 0x2, 0x3, 0x0]))
+    );
+}
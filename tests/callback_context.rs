@@ -0,0 +1,122 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{spi, RegisterValue, SPI};
+use regmock_rs::utils::{Callback, RegisterAccessType, Regmock, WriteFunction};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+#[test]
+fn context_describes_access() {
+    let seen = Arc::new(Mutex::new(Vec::new()));
+    let recorded = seen.clone();
+    let mut mock = Regmock::from_svd(SVD_PATH).unwrap();
+    mock.write_callbacks.insert(
+        SPI.ctrl().addr(),
+        Callback::new(move |ctx| {
+            recorded.lock().unwrap().push((
+                ctx.ty.clone(),
+                ctx.addr,
+                ctx.len,
+                ctx.before,
+                ctx.value,
+                ctx.reg_name().map(str::to_owned),
+                ctx.log().len_full(),
+            ));
+            ctx.value | 0x4
+        }),
+    );
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+    regmock_rs::write_fn(SPI.ctrl().addr(), 1, 0x3);
+
+    assert_eq!(
+        *seen.lock().unwrap(),
+        vec![
+            (
+                RegisterAccessType::WRITE,
+                SPI.ctrl().addr(),
+                4,
+                0x0,
+                0x1,
+                Some("SPI.ctrl()".to_owned()),
+                0
+            ),
+            (
+                RegisterAccessType::WRITE,
+                SPI.ctrl().addr(),
+                1,
+                0x5,
+                0x3,
+                Some("SPI.ctrl()".to_owned()),
+                1
+            ),
+        ]
+    );
+    assert_eq!(unsafe { SPI.ctrl().read().get_raw() }, 0x7);
+}
+
+#[test]
+fn legacy_closures_through_adapter() {
+    let mut mock = Regmock::default();
+    let legacy: WriteFunction = Box::new(|registers, _, val| {
+        registers.insert(0x104, val * 2);
+        val
+    });
+    mock.write_callbacks.insert(0x100, legacy.into());
+    mock.write_volatile(0x100, 4, 0x21);
+    assert_eq!(mock.register_mocks[&0x104], 0x42);
+}
+
+#[test]
+fn callbacks_take_precedence_over_read_fn() {
+    let mut mock = Regmock::default();
+    mock.read_fn.insert(0x100, Box::new(|_, _| 0x1));
+    mock.read_callbacks
+        .insert(0x100, Callback::new(|ctx| ctx.before + 0x2));
+    assert_eq!(mock.read_volatile(0x100, 4), 0x2);
+}
+
+#[test]
+fn disable_logging_from_callback() {
+    let mut mock = Regmock::default();
+    mock.read_callbacks.insert(
+        0x100,
+        Callback::new(|ctx| {
+            ctx.set_logging(false);
+            ctx.before
+        }),
+    );
+    mock.write_volatile(0x100, 4, 0x1);
+    let _ = mock.read_volatile(0x100, 4);
+    mock.write_volatile(0x100, 4, 0x2);
+    assert_eq!(mock.log.len_full(), 1);
+}
+
+#[test]
+fn scheduled_follow_up_accesses() {
+    let mut mock = Regmock::default();
+    // writing the start bit completes a transfer by setting the done bit
+    mock.write_callbacks.insert(
+        0x100,
+        Callback::new(|ctx| {
+            if ctx.value & 0x1 != 0 {
+                ctx.schedule(|mock| {
+                    let status = mock.read_volatile(0x104, 4);
+                    mock.write_volatile(0x104, 4, status | 0x80);
+                });
+            }
+            ctx.value & !0x1
+        }),
+    );
+    mock.write_volatile(0x100, 4, 0x1);
+
+    assert_eq!(mock.register_mocks[&0x100], 0x0);
+    assert_eq!(mock.register_mocks[&0x104], 0x80);
+    let addrs: Vec<_> = mock.log.iter().map(|a| a.addr.unwrap()).collect();
+    assert_eq!(addrs, vec![0x100, 0x104, 0x104]);
+}
@@ -0,0 +1,139 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{spi, RegisterValue, GPIO, SPI, TIMER};
+use regmock_rs::database::{Access, RegisterDatabase};
+use regmock_rs::matchers::{LogMatcher, LogSequenceMatcher};
+use regmock_rs::utils::access_gen::write_value;
+use regmock_rs::utils::{RegisterAccess, RegisterAccessType, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+#[test]
+fn register_metadata() {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    assert_eq!(database.len(), 13);
+
+    let status = database.get(SPI.status().addr()).unwrap();
+    assert_eq!(status.name, "SPI.status()");
+    assert_eq!(status.peripheral, "SPI");
+    assert_eq!(status.size, 32);
+    assert_eq!(status.access, Access::ReadWrite);
+    assert_eq!(status.fields.len(), 11);
+    let rx_fill = status.fields.iter().find(|f| f.name == "rx_fill").unwrap();
+    assert_eq!((rx_fill.bit_offset, rx_fill.bit_width), (16, 4));
+    assert_eq!(rx_fill.access, Access::ReadOnly);
+    assert_eq!(rx_fill.mask(), 0xF_0000);
+    assert_eq!(rx_fill.extract(0x3_0004), 3);
+
+    let r#in = database.get(GPIO.r#in().addr()).unwrap();
+    assert_eq!(r#in.access, Access::ReadOnly);
+    assert_eq!(r#in.reset_mask, 0xFFF);
+
+    assert_eq!(
+        database.name(TIMER.timercluster()[1].max().addr()),
+        Some("TIMER.timercluster()[1].max()")
+    );
+    assert_eq!(database.name(0x1234), None);
+}
+
+#[test]
+fn bit_range_styles() {
+    let database = RegisterDatabase::from_svd_str(
+        r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>r</name>
+                    <addressOffset>0x0</addressOffset>
+                    <access>write-only</access>
+                    <fields>
+                        <field><name>c</name><bitRange>[15:8]</bitRange></field>
+                        <field><name>b</name><lsb>4</lsb><msb>7</msb></field>
+                        <field><name>a</name><bitOffset>0</bitOffset><bitWidth>4</bitWidth></field>
+                    </fields>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#,
+    )
+    .unwrap();
+    let register = database.get(0x100).unwrap();
+    assert_eq!(register.access, Access::WriteOnly);
+    let layout: Vec<_> = register
+        .fields
+        .iter()
+        .map(|f| (f.name.as_str(), f.bit_offset, f.bit_width, f.access))
+        .collect();
+    assert_eq!(
+        layout,
+        vec![
+            ("a", 0, 4, Access::WriteOnly),
+            ("b", 4, 4, Access::WriteOnly),
+            ("c", 8, 8, Access::WriteOnly)
+        ]
+    );
+    assert_eq!(register.describe_value(0xAB12), "a=0x2 b=0x1 c=0xab");
+}
+
+#[test]
+fn names_from_database() {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    let mock = Regmock::with_database(database);
+    assert_eq!(mock.get_reg_name(SPI.ctrl().addr()), Some("SPI.ctrl()"));
+    assert_eq!(mock.get_reg_name(0x1234), None);
+
+    // names from the phf resolver are stripped of their padding
+    let mock = Regmock::with_resolver(&test_pac::reg_name::reg_name_from_addr);
+    assert_eq!(mock.get_reg_name(GPIO.r#in().addr()), Some("GPIO.r#in()"));
+}
+
+#[test]
+fn format_log_with_names() {
+    let mock = Regmock::from_svd(SVD_PATH).unwrap();
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        SPI.ctrl().write(spi::Ctrl::new(0x3));
+        let _ = SPI.ctrl().read();
+        let _ = SPI.ctrl().read();
+    }
+
+    let access = RegisterAccess::new(RegisterAccessType::WRITE, SPI.ctrl().addr(), 4, 0, 3);
+    let text = regmock_rs::with_mock(|m| m.format_access(&access)).unwrap();
+    assert_eq!(
+        text,
+        "WRITE SPI.ctrl() (0x00008204) len:4 0x00000000 -> 0x00000003 [en=0x1 cpha=0x1 cpol=0x0]"
+    );
+
+    let log = regmock_rs::with_mock(|m| m.format_log()).unwrap();
+    let lines: Vec<_> = log.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[1].starts_with("READ SPI.ctrl() (0x00008204)"));
+    assert!(lines[1].ends_with("(x2)"));
+}
+
+#[test]
+fn matcher_reports_register_names() {
+    let mock = Regmock::from_svd(SVD_PATH).unwrap();
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+
+    let expected = write_value(SPI.ctrl().addr(), 0x2);
+    let logs = regmock_rs::logs();
+    let error = LogSequenceMatcher::new(vec![&expected])
+        .r#match(logs.iter())
+        .unwrap_err();
+    assert!(error.reason.contains("SPI.ctrl()"), "{}", error.reason);
+    assert_eq!(unsafe { SPI.ctrl().read().get_raw() }, 0x1);
+}
@@ -0,0 +1,145 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, spi, RegisterValue, GPIO, SPI};
+use regmock_rs::expectation::Sequence;
+use regmock_rs::install;
+use regmock_rs::utils::Regmock;
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+fn new_mock() -> Arc<Mutex<Regmock>> {
+    Arc::new(Mutex::new(Regmock::default()))
+}
+
+#[test]
+fn accesses_are_counted() {
+    init_mock(None);
+    let guard = install(new_mock());
+    {
+        let mut mock = guard.mock().lock().unwrap();
+        mock.register_mocks.insert(SPI.status().addr(), 0x4);
+        mock.expect_write(SPI.ctrl().addr())
+            .with_value(0x1)
+            .times(2);
+        mock.expect_read(SPI.status().addr()).with_value(0x4);
+    }
+
+    unsafe {
+        SPI.ctrl().write(spi::Ctrl::new(0x1));
+        SPI.ctrl().write(spi::Ctrl::new(0x1));
+        let _ = SPI.status().read();
+        // registers without expectations can be accessed freely
+        GPIO.out().write(gpio::Out::new(0x1));
+    }
+    assert_eq!(guard.mock().lock().unwrap().verify(), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "Unexpected register access")]
+fn unexpected_value_panics() {
+    init_mock(None);
+    let _guard = install(new_mock());
+    regmock_rs::with_mock(|mock| {
+        mock.expect_write(SPI.ctrl().addr()).with_value(0x1);
+    })
+    .unwrap();
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x2)) };
+}
+
+#[test]
+fn too_many_accesses_panic() {
+    let mut mock = Regmock::default();
+    mock.expect_write(0x100).times(1);
+    mock.write_volatile(0x100, 4, 0x1);
+    let line = line!() + 1;
+    let panic = catch_unwind(AssertUnwindSafe(|| mock.write_volatile(0x100, 4, 0x1)));
+
+    let message = panic.unwrap_err().downcast::<String>().unwrap();
+    assert!(message
+        .starts_with("Unexpected register access WRITE 0x00000100 len:4 0x00000001 -> 0x00000001"));
+    assert!(message.contains(&format!("at {}:{line}:", file!())));
+    assert!(message.contains("(seq 1), expected:"));
+}
+
+#[test]
+#[should_panic(expected = "access to 0x00000100..0x00000110 never")]
+fn forbidden_range_panics() {
+    let mut mock = Regmock::default();
+    mock.expect_no_access(0x100..0x110);
+    let _ = mock.read_volatile(0x0FC, 4);
+    let _ = mock.read_volatile(0x10C, 4);
+}
+
+#[test]
+fn silent_accesses_are_not_checked() {
+    let mut mock = Regmock::default();
+    mock.expect_no_access(0x100..0x110);
+    mock.callback_enabled = false;
+    mock.write_volatile(0x100, 4, 0x1);
+    mock.callback_enabled = true;
+    assert_eq!(mock.verify(), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "out of sequence")]
+fn out_of_sequence_panics() {
+    let mut mock = Regmock::default();
+    let seq = Sequence::new();
+    mock.expect_write(0x100).in_sequence(&seq);
+    mock.expect_write(0x104).in_sequence(&seq);
+    mock.write_volatile(0x104, 4, 0x1);
+}
+
+#[test]
+#[should_panic(expected = "out of sequence")]
+fn sequence_can_not_go_back() {
+    let mut mock = Regmock::default();
+    let seq = Sequence::new();
+    mock.expect_write(0x100).in_sequence(&seq);
+    mock.expect_write(0x104).in_sequence(&seq);
+    mock.write_volatile(0x100, 4, 0x1);
+    mock.write_volatile(0x104, 4, 0x1);
+    mock.write_volatile(0x100, 4, 0x1);
+}
+
+#[test]
+fn unmet_expectations_are_reported() {
+    let mut mock = Regmock::default();
+    let line = line!() + 1;
+    mock.expect_write(0x100).with_value(0x1).times(2);
+    mock.write_volatile(0x100, 4, 0x1);
+
+    let failures = mock.verify().unwrap_err();
+    assert_eq!(failures.len(), 1);
+    assert!(failures[0].contains("write to 0x00000100 with value 0x1 2 time(s)"));
+    assert!(failures[0].contains(&format!("{}:{line}", file!())));
+    assert!(failures[0].ends_with("got 1 time(s)"));
+}
+
+#[test]
+#[should_panic(expected = "unmet expectation")]
+fn guard_drop_verifies() {
+    init_mock(None);
+    let guard = install(new_mock());
+    guard.mock().lock().unwrap().expect_write(SPI.ctrl().addr());
+}
+
+#[test]
+#[should_panic(expected = "Regmock replaced by init_regmock failed verification")]
+fn replaced_base_mock_is_verified() {
+    let mock = init_mock(None);
+    mock.lock().unwrap().expect_write(SPI.ctrl().addr());
+    drop(mock);
+    // e.g. the next test on a reused thread
+    init_mock(None);
+}
+
+#[test]
+#[should_panic(expected = "unmet expectation")]
+fn mock_drop_verifies() {
+    let mut mock = Regmock::default();
+    mock.expect_read(0x100).at_least(1);
+}
@@ -0,0 +1,120 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{spi, RegisterValue, SPI};
+use regmock_rs::database::{ReadAction, WriteAction};
+use regmock_rs::utils::{FieldSemantics, RegisterAccess, RegisterAccessType, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+static SEMANTICS_SVD: &str = r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>flags</name>
+                    <addressOffset>0x0</addressOffset>
+                    <size>16</size>
+                    <fields>
+                        <field><name>w1c</name><bitRange>[3:0]</bitRange><modifiedWriteValues>oneToClear</modifiedWriteValues></field>
+                        <field><name>w0c</name><bitRange>[5:4]</bitRange><modifiedWriteValues>zeroToClear</modifiedWriteValues></field>
+                        <field><name>w1s</name><bitRange>[7:6]</bitRange><modifiedWriteValues>oneToSet</modifiedWriteValues></field>
+                        <field><name>ro</name><bitRange>[9:8]</bitRange><access>read-only</access></field>
+                        <field><name>rw</name><bitRange>[11:10]</bitRange></field>
+                    </fields>
+                </register>
+                <register>
+                    <name>events</name>
+                    <addressOffset>0x4</addressOffset>
+                    <fields>
+                        <field><name>rc</name><bitRange>[7:0]</bitRange><readAction>clear</readAction></field>
+                        <field><name>rs</name><bitRange>[15:8]</bitRange><readAction>set</readAction></field>
+                        <field><name>data</name><bitRange>[31:16]</bitRange><readAction>modifyExternal</readAction></field>
+                    </fields>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+
+#[test]
+fn write_semantics_from_svd() {
+    let mut mock = Regmock::from_svd_str(SEMANTICS_SVD).unwrap();
+    mock.register_mocks.insert(0x100, 0x0F3F);
+
+    mock.write_volatile(0x100, 2, 0xF5C5);
+    // w1c: 0xF & !0x5 = 0xA
+    // w0c: 0x3 & 0x0 = 0x0
+    // w1s: 0x0 | 0x3 = 0x3
+    // ro: unchanged 0x3
+    // rw: 0x1
+    // reserved bits [15:12]: unchanged 0x0
+    let expected = 0xA | (0x3 << 6) | (0x3 << 8) | (0x1 << 10);
+    assert_eq!(mock.register_mocks[&0x100], expected);
+    assert_eq!(
+        mock.log.log.last().unwrap().0,
+        RegisterAccess::new(RegisterAccessType::WRITE, 0x100, 2, 0x0F3F, expected)
+    );
+}
+
+#[test]
+fn read_semantics_from_svd() {
+    let mut mock = Regmock::from_svd_str(SEMANTICS_SVD).unwrap();
+    mock.register_mocks.insert(0x104, 0x1234_0055);
+
+    assert_eq!(mock.read_volatile(0x104, 4), 0x1234_0055);
+    assert_eq!(mock.register_mocks[&0x104], 0x1234_FF00);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x1234_FF00);
+}
+
+#[test]
+fn silent_accesses_bypass_semantics() {
+    let mut mock = Regmock::from_svd_str(SEMANTICS_SVD).unwrap();
+    mock.callback_enabled = false;
+    mock.register_mocks.insert(0x104, 0x55);
+
+    mock.write_volatile(0x100, 2, 0xFFFF);
+    assert_eq!(mock.register_mocks[&0x100], 0xFFFF);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x55);
+    assert_eq!(mock.register_mocks[&0x104], 0x55);
+}
+
+#[test]
+fn user_declared_semantics() {
+    let mut mock = Regmock::default();
+    mock.field_semantics.insert(
+        0x200,
+        vec![
+            FieldSemantics::on_write(0xF0, WriteAction::OneToToggle),
+            FieldSemantics::on_read(0x0F, ReadAction::Clear),
+        ],
+    );
+    mock.register_mocks.insert(0x200, 0x3F);
+
+    mock.write_volatile(0x200, 4, 0x1F);
+    assert_eq!(mock.register_mocks[&0x200], 0x2F);
+    assert_eq!(mock.read_volatile(0x200, 4), 0x2F);
+    assert_eq!(mock.register_mocks[&0x200], 0x20);
+}
+
+#[test]
+fn write_one_to_clear_through_pac() {
+    let mock = Regmock::from_svd(SVD_PATH).unwrap();
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    // the test sets the flush bit, the DUT clears it by writing 1
+    regmock_rs::silent(|| unsafe { SPI.status().write(spi::Status::new(0x8000_0000)) });
+    unsafe { SPI.status().write(spi::Status::new(0x8000_0000)) };
+
+    assert_eq!(
+        regmock_rs::silent(|| unsafe { SPI.status().read() }).get_raw(),
+        0x0
+    );
+}
@@ -0,0 +1,147 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::harness::TestHarness;
+use regmock_rs::matchers::{LogMatcher, LogSequenceMatcher};
+use regmock_rs::utils::access_gen::write_value;
+use regmock_rs::utils::Regmock;
+use regmock_rs::{install, MockError};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+fn new_mock() -> Arc<Mutex<Regmock>> {
+    Arc::new(Mutex::new(Regmock::default()))
+}
+
+#[test]
+fn nested_installs_restore_previous_mock() {
+    let base = init_mock(None);
+    unsafe { GPIO.we().write(gpio::We::new(0x1)) };
+
+    {
+        let outer = install(new_mock());
+        unsafe { GPIO.we().write(gpio::We::new(0x2)) };
+        {
+            let inner = install(new_mock());
+            assert_eq!(unsafe { GPIO.we().read().get_raw() }, 0x0);
+            assert_eq!(inner.mock().lock().unwrap().log.len_full(), 1);
+        }
+        assert_eq!(unsafe { GPIO.we().read().get_raw() }, 0x2);
+        assert_eq!(outer.mock().lock().unwrap().log.len_full(), 2);
+    }
+
+    assert_eq!(unsafe { GPIO.we().read().get_raw() }, 0x1);
+    assert_eq!(base.lock().unwrap().log.len_full(), 2);
+}
+
+#[test]
+fn thread_can_be_reused() {
+    for value in 1..4 {
+        let guard = install(new_mock());
+        assert_eq!(regmock_rs::read_fn(0x100, 4), 0x0);
+        regmock_rs::write_fn(0x100, 4, value);
+        assert_eq!(guard.mock().lock().unwrap().register_mocks[&0x100], value);
+    }
+    assert!(matches!(
+        regmock_rs::with_mock(|_| ()),
+        Err(MockError::MockNotInitialized)
+    ));
+}
+
+#[test]
+fn init_regmock_can_be_called_repeatedly() {
+    regmock_rs::init_regmock(new_mock());
+    regmock_rs::write_fn(0x100, 4, 0x1);
+    regmock_rs::init_regmock(new_mock());
+    assert_eq!(regmock_rs::read_fn(0x100, 4), 0x0);
+}
+
+#[test]
+fn dropping_outer_guard_uninstalls_inner_mocks() {
+    let outer = install(new_mock());
+    let inner = install(new_mock());
+    drop(outer);
+    assert!(regmock_rs::with_mock(|_| ()).is_err());
+    drop(inner);
+    assert!(regmock_rs::with_mock(|_| ()).is_err());
+}
+
+#[test]
+fn dropping_uninstalled_guard_keeps_later_installations() {
+    let a = install(new_mock());
+    let b = install(new_mock());
+    drop(a);
+    let c = install(new_mock());
+    let d = install(new_mock());
+    drop(b);
+    regmock_rs::write_fn(0x100, 4, 0x1);
+    assert_eq!(d.mock().lock().unwrap().log.len_full(), 1);
+    assert_eq!(c.mock().lock().unwrap().log.len_full(), 0);
+}
+
+/// Require a single write of `0x2` to `GPIO.out()` at the end of the test.
+fn require_gpio_out_written(mock: &mut Regmock) {
+    mock.at_end_of_test(|mock| {
+        let expected = write_value(GPIO.out().addr(), 0x2);
+        LogSequenceMatcher::new(vec![&expected])
+            .r#match(mock.log.iter())
+            .map_err(|e| e.to_string())
+    });
+}
+
+#[test]
+#[should_panic(expected = "WRITE GPIO.out() (0x0000842C)")]
+fn matchers_in_end_of_test_checks_of_guard_name_registers() {
+    init_mock(None);
+    let guard = install(Arc::new(Mutex::new(Regmock::with_resolver(
+        &pac::reg_name::reg_name_from_addr,
+    ))));
+    require_gpio_out_written(&mut guard.mock().lock().unwrap());
+    unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+}
+
+#[test]
+#[should_panic(expected = "WRITE GPIO.out() (0x0000842C)")]
+fn matchers_in_end_of_test_checks_of_harness_name_registers() {
+    init_mock(None);
+    let harness = TestHarness::new(Regmock::with_resolver(&pac::reg_name::reg_name_from_addr));
+    require_gpio_out_written(&mut harness.mock().lock().unwrap());
+    unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+}
+
+#[test]
+fn end_of_test_checks_name_registers_with_the_resolver() {
+    let mut mock = Regmock::with_resolver(&pac::reg_name::reg_name_from_addr);
+    mock.at_end_of_test(|mock| Err(mock.get_reg_name(GPIO.out().addr()).unwrap().to_owned()));
+    assert_eq!(mock.verify(), Err(vec!["GPIO.out()".to_owned()]));
+    // the resolver is given back after the checks
+    assert_eq!(mock.get_reg_name(GPIO.out().addr()), Some("GPIO.out()"));
+}
+
+#[test]
+fn harness_of_test_returning_an_error_prints_the_log_instead_of_verifying() {
+    init_mock(None);
+    let harness = TestHarness::new(Regmock::default());
+    harness
+        .mock()
+        .lock()
+        .unwrap()
+        .at_end_of_test(|_| Err("not verified".to_owned()));
+    let result: Result<(), String> = harness.finish(Err("test failed".to_owned()));
+    assert_eq!(result, Err("test failed".to_owned()));
+}
+
+#[test]
+#[should_panic(expected = "not met")]
+fn harness_of_passing_test_verifies() {
+    init_mock(None);
+    let harness = TestHarness::new(Regmock::default());
+    harness
+        .mock()
+        .lock()
+        .unwrap()
+        .at_end_of_test(|_| Err("not met".to_owned()));
+    let _: Result<(), String> = harness.finish(Ok(()));
+}
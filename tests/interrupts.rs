@@ -0,0 +1,158 @@
+use std::cell::Cell;
+use std::ops::Range;
+use std::time::Duration;
+
+use pac::{gpio, spi, timer, RegisterValue, GPIO, SPI, TIMER};
+use regmock_rs::interrupt::{EnableBit, InterruptEventKind};
+use regmock_rs::model::PeripheralModel;
+use regmock_rs::utils::{Callback, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SPI_IRQ: usize = 3;
+const TIMER_IRQ: usize = 7;
+
+thread_local! {
+    static RECEIVED: Cell<Option<u8>> = const { Cell::new(None) };
+    static TIMER_ISR_CALLS: Cell<usize> = const { Cell::new(0) };
+}
+
+/// Receive interrupt handler of the DUT.
+fn spi_rx_isr() {
+    let data = unsafe { SPI.rx().read().data().get() };
+    RECEIVED.set(Some(data));
+}
+
+fn timer_isr() {
+    TIMER_ISR_CALLS.set(TIMER_ISR_CALLS.get() + 1);
+}
+
+/// Raises the SPI interrupt when a transfer is started.
+fn raise_on_transfer(mock: &mut Regmock) {
+    mock.register_mocks.insert(SPI.rx().addr(), 0x42);
+    mock.write_callbacks.insert(
+        SPI.tx().addr(),
+        Callback::new(|ctx| {
+            ctx.raise_interrupt(SPI_IRQ);
+            ctx.value
+        }),
+    );
+}
+
+#[test]
+fn isr_runs_between_accesses() {
+    let mock = init_mock(None);
+    {
+        let mut mock = mock.lock().unwrap();
+        raise_on_transfer(&mut mock);
+        mock.interrupts.connect(SPI_IRQ, spi_rx_isr, None);
+    }
+
+    unsafe {
+        SPI.tx().write(spi::Tx::new(0x1));
+        GPIO.out().write(gpio::Out::new(0x1));
+    }
+    assert_eq!(RECEIVED.get(), Some(0x42));
+
+    let logs = regmock_rs::logs();
+    let addrs: Vec<_> = logs.iter().map(|a| a.addr.unwrap()).collect();
+    assert_eq!(
+        addrs,
+        vec![SPI.tx().addr(), SPI.rx().addr(), GPIO.out().addr()]
+    );
+    let events: Vec<_> = logs
+        .interrupts
+        .iter()
+        .map(|e| (e.irq, e.kind, e.log_index))
+        .collect();
+    assert_eq!(
+        events,
+        vec![
+            (SPI_IRQ, InterruptEventKind::Enter, 1),
+            (SPI_IRQ, InterruptEventKind::Exit, 2)
+        ]
+    );
+}
+
+#[test]
+fn disabled_interrupts_stay_pending() {
+    let mock = init_mock(None);
+    {
+        let mut mock = mock.lock().unwrap();
+        raise_on_transfer(&mut mock);
+        mock.interrupts.connect(
+            SPI_IRQ,
+            spi_rx_isr,
+            Some(EnableBit::new(SPI.ctrl().addr(), 0x1 << 8)),
+        );
+    }
+
+    unsafe { SPI.tx().write(spi::Tx::new(0x1)) };
+    assert_eq!(RECEIVED.get(), None);
+    assert!(mock.lock().unwrap().interrupts.is_pending(SPI_IRQ));
+
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1 << 8)) };
+    assert_eq!(RECEIVED.get(), Some(0x42));
+    assert!(!mock.lock().unwrap().interrupts.is_pending(SPI_IRQ));
+}
+
+/// Raises an interrupt every millisecond.
+struct PeriodicTimer {
+    elapsed: Duration,
+}
+
+impl PeripheralModel for PeriodicTimer {
+    fn range(&self) -> Range<usize> {
+        let base = TIMER.timercluster()[0].ctrlstat().addr();
+        base..base + 0xc
+    }
+
+    fn on_tick(&mut self, mock: &mut Regmock, elapsed: Duration) {
+        self.elapsed += elapsed;
+        if self.elapsed >= Duration::from_millis(1) {
+            self.elapsed = Duration::ZERO;
+            mock.raise_interrupt(TIMER_IRQ);
+        }
+    }
+}
+
+#[test]
+fn model_raises_interrupt() {
+    let mock = init_mock(None);
+    {
+        let mut mock = mock.lock().unwrap();
+        mock.attach_model(PeriodicTimer {
+            elapsed: Duration::ZERO,
+        });
+        mock.interrupts.connect(TIMER_IRQ, timer_isr, None);
+    }
+
+    regmock_rs::advance_time(Duration::from_micros(1500));
+    // test-side accesses do not dispatch interrupts
+    regmock_rs::silent(|| unsafe { TIMER.timercluster()[0].count().read() });
+    assert_eq!(TIMER_ISR_CALLS.get(), 0);
+
+    unsafe {
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x1))
+    };
+    assert_eq!(TIMER_ISR_CALLS.get(), 1);
+    unsafe {
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x1))
+    };
+    assert_eq!(TIMER_ISR_CALLS.get(), 1);
+}
+
+#[test]
+fn reset_clears_pending_interrupts() {
+    let mut mock = Regmock::default();
+    mock.interrupts.connect(TIMER_IRQ, timer_isr, None);
+    mock.raise_interrupt(TIMER_IRQ);
+    mock.reset();
+    assert!(mock.enter_interrupt().is_none());
+}
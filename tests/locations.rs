@@ -0,0 +1,107 @@
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::utils::Regmock;
+use std::sync::{Arc, Mutex};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+/// Driver code whose accesses are located.
+fn toggle_led() -> u32 {
+    let line = line!() + 1;
+    unsafe { GPIO.out().modify(|r| r.set_raw(r.get_raw() ^ 0x1)) };
+    line
+}
+
+#[test]
+#[cfg(feature = "backtrace")]
+fn accesses_are_located_in_driver_code() {
+    let mock = init_mock(None);
+    mock.lock().unwrap().capture_locations = true;
+
+    let line = toggle_led();
+
+    let logs = regmock_rs::logs();
+    assert_eq!(logs.len_full(), 2);
+    for access in logs.iter() {
+        let location = access.location.as_ref().unwrap().resolve().unwrap();
+        assert_eq!(location.file, file!());
+        assert_eq!(location.line, line);
+    }
+}
+
+#[test]
+fn direct_accesses_are_located_by_track_caller() {
+    let mut mock = Regmock::default();
+    mock.capture_locations = true;
+
+    let line = line!() + 1;
+    mock.write_volatile(0x100, 4, 0x1);
+    let _guard = regmock_rs::install(Arc::new(Mutex::new(mock)));
+    let read_line = line!() + 1;
+    let _ = regmock_rs::read_fn(0x100, 4);
+
+    let logs = regmock_rs::logs();
+    let locations: Vec<_> = logs
+        .iter()
+        .map(|access| access.location.as_ref().unwrap())
+        .collect();
+    // the caller is known without capturing a backtrace
+    #[cfg(feature = "backtrace")]
+    assert!(locations.iter().all(|l| l.backtrace().is_none()));
+    assert_eq!(locations[0].caller().unwrap().line(), line);
+    assert_eq!(locations[1].caller().unwrap().line(), read_line);
+    assert_eq!(locations[1].resolve().unwrap().file, file!());
+}
+
+#[test]
+#[cfg(feature = "backtrace")]
+fn locations_are_shown_in_matcher_failures() {
+    use regmock_rs::matchers::{LogMatcher, LogSequenceMatcher};
+    use regmock_rs::utils::access_gen::write_value;
+
+    let mock = init_mock(None);
+    mock.lock().unwrap().capture_locations = true;
+
+    let line = line!() + 1;
+    unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+
+    let expected = write_value(GPIO.out().addr(), 0x2);
+    let logs = regmock_rs::logs();
+    let error = LogSequenceMatcher::new(vec![&expected])
+        .r#match(logs.iter())
+        .unwrap_err();
+    let location = format!("at {}:{line}:", file!());
+    assert!(error.reason.contains(&location), "{}", error.reason);
+    assert!(mock.lock().unwrap().format_log().contains(&location));
+}
+
+#[test]
+#[cfg(not(feature = "backtrace"))]
+fn accesses_through_the_pac_need_backtraces() {
+    let mock = init_mock(None);
+    mock.lock().unwrap().capture_locations = true;
+
+    toggle_led();
+    assert!(regmock_rs::logs().iter().all(|a| a.location.is_none()));
+}
+
+#[test]
+fn polling_reads_are_merged() {
+    let mock = init_mock(None);
+    mock.lock().unwrap().capture_locations = true;
+
+    for _ in 0..3 {
+        let _ = unsafe { GPIO.r#in().read() };
+    }
+    let logs = regmock_rs::logs();
+    assert_eq!(logs.log.len(), 1);
+    assert_eq!(logs.log[0].1, 3);
+}
+
+#[test]
+fn locations_are_not_captured_by_default() {
+    init_mock(None);
+    unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+    assert!(regmock_rs::logs().log[0].0.location.is_none());
+}
@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, timer, RegisterValue, GPIO, TIMER};
+use regmock_rs::diff::DiffEntry;
+use regmock_rs::install;
+use regmock_rs::utils::Regmock;
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+/// Run `driver` against a fresh mock with register metadata.
+fn run(driver: impl FnOnce()) -> Arc<Mutex<Regmock>> {
+    let mock = Arc::new(Mutex::new(Regmock::from_svd(SVD_PATH).unwrap()));
+    let _guard = install(mock.clone());
+    driver();
+    mock
+}
+
+fn start_timer(ctrlstat: u32, polls: usize) {
+    unsafe {
+        GPIO.out().write(gpio::Out::new(0x1));
+        for _ in 0..polls {
+            let _ = GPIO.r#in().read();
+        }
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(ctrlstat));
+    }
+}
+
+#[test]
+fn identical_runs_have_no_differences() {
+    init_mock(None);
+    let old = run(|| start_timer(0x1, 2));
+    let new = run(|| start_timer(0x1, 2));
+
+    let diff = old.lock().unwrap().log.diff(&new.lock().unwrap().log);
+    assert!(diff.is_empty());
+    assert_eq!(diff.entries.len(), 3);
+    assert_eq!(diff.to_string(), "  ... 3 unchanged");
+}
+
+#[test]
+fn changed_values_are_shown_per_bitfield() {
+    init_mock(None);
+    let old = run(|| start_timer(0x1, 2));
+    let new = run(|| start_timer(0x21, 2));
+
+    let new = new.lock().unwrap();
+    let diff = old.lock().unwrap().log.diff(&new.log);
+    assert_eq!(diff.changes().count(), 1);
+    assert_eq!(
+        new.format_diff(&diff),
+        "  WRITE GPIO.out() (0x0000842C) len:4 0x00000000 -> 0x00000001\n  \
+         READ GPIO.r#in() (0x00008420) len:4 0x00000000 -> 0x00000000 (x2)\n\
+         ~ WRITE TIMER.timercluster()[0].ctrlstat() (0x00008000) len:4: \
+         after 0x00000001 -> 0x00000021 [clock: 0x0 -> 0x2]"
+    );
+}
+
+#[test]
+fn inserted_removed_and_polled_accesses_are_reported() {
+    init_mock(None);
+    let old = run(|| start_timer(0x1, 2));
+    let new = run(|| unsafe {
+        let _ = GPIO.r#in().read();
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x1));
+        GPIO.out().write(gpio::Out::new(0x0));
+    });
+
+    let new = new.lock().unwrap();
+    let diff = old.lock().unwrap().log.diff(&new.log);
+    assert!(matches!(diff.entries[0], DiffEntry::Removed(..)));
+    assert!(matches!(
+        diff.entries[1],
+        DiffEntry::Changed {
+            old: (_, 2),
+            new: (_, 1)
+        }
+    ));
+    assert!(matches!(diff.entries[2], DiffEntry::Unchanged(..)));
+    assert!(matches!(diff.entries[3], DiffEntry::Inserted(..)));
+    assert_eq!(
+        new.format_diff(&diff),
+        "- WRITE GPIO.out() (0x0000842C) len:4 0x00000000 -> 0x00000001\n\
+         ~ READ GPIO.r#in() (0x00008420) len:4: x2 -> x1\n  \
+         WRITE TIMER.timercluster()[0].ctrlstat() (0x00008000) len:4 0x00000000 -> 0x00000001\n\
+         + WRITE GPIO.out() (0x0000842C) len:4 0x00000000 -> 0x00000000"
+    );
+}
+
+#[test]
+fn long_unchanged_runs_are_collapsed() {
+    let mut old = Regmock::default();
+    let mut new = Regmock::default();
+    for addr in (0x100..0x120).step_by(4) {
+        old.write_volatile(addr, 4, 0x1);
+        new.write_volatile(addr, 4, 0x1);
+    }
+    new.write_volatile(0x200, 4, 0x1);
+
+    assert_eq!(
+        old.log.diff(&new.log).to_string(),
+        "  ... 6 unchanged\n  \
+         WRITE 0x00000118 len:4 0x00000000 -> 0x00000001\n  \
+         WRITE 0x0000011C len:4 0x00000000 -> 0x00000001\n\
+         + WRITE 0x00000200 len:4 0x00000000 -> 0x00000001"
+    );
+}
+
+#[test]
+fn long_logs_with_changes_at_both_ends_are_aligned() {
+    let mut old = Regmock::default();
+    let mut new = Regmock::default();
+    new.write_volatile(0x200, 4, 0x1);
+    for i in 0..2000 {
+        old.write_volatile(0x1000 + 4 * i, 4, 0x1);
+        new.write_volatile(0x1000 + 4 * i, 4, 0x1);
+    }
+    old.write_volatile(0x200, 4, 0x1);
+
+    let diff = old.log.diff(&new.log);
+    assert_eq!(diff.entries.len(), 2002);
+    assert!(matches!(diff.entries[0], DiffEntry::Inserted(..)));
+    assert!(matches!(diff.entries[2001], DiffEntry::Removed(..)));
+    assert_eq!(diff.changes().count(), 2);
+}
@@ -0,0 +1,97 @@
+use std::thread;
+use std::time::Duration;
+
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::utils::access_gen::write_value;
+use regmock_rs::utils::Regmock;
+use regmock_rs::{given, require_seq};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+#[test]
+fn entries_are_numbered() {
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x100, 4, 0x1);
+    for _ in 0..3 {
+        let _ = mock.read_volatile(0x104, 4);
+    }
+    mock.write_volatile(0x100, 4, 0x2);
+    let seqs: Vec<_> = mock.log.iter().map(|a| a.seq.unwrap()).collect();
+    assert_eq!(seqs, vec![0, 1, 4]);
+
+    // unlogged accesses are not numbered
+    mock.log_enabled = false;
+    mock.write_volatile(0x100, 4, 0x3);
+    mock.log_enabled = true;
+    mock.write_volatile(0x100, 4, 0x4);
+    assert_eq!(mock.log.log.last().unwrap().0.seq, Some(5));
+}
+
+#[test]
+fn polling_runs_keep_first_and_last_time() {
+    let mut mock = Regmock::default();
+    mock.read_cost = Duration::from_micros(1);
+    for _ in 0..5 {
+        let _ = mock.read_volatile(0x104, 4);
+    }
+    let (run, count) = &mock.log.log[0];
+    assert_eq!(*count, 5);
+    assert_eq!(run.time, Some(Duration::ZERO));
+    assert_eq!(run.last_time, Some(Duration::from_micros(4)));
+    assert!(run.wall_time.unwrap() <= run.last_wall_time.unwrap());
+}
+
+#[test]
+fn accesses_are_attributed_to_threads() {
+    let mock = init_mock(None);
+    let dut = thread::spawn({
+        let mock = mock.clone();
+        move || {
+            init_mock(Some(mock));
+            unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+            thread::current().id()
+        }
+    });
+    let dut = dut.join().unwrap();
+    unsafe { GPIO.out().write(gpio::Out::new(0x2)) };
+
+    let logs = regmock_rs::logs();
+    assert_eq!(logs.len_full(), 2);
+    assert_eq!(logs.iter_thread(dut).count(), 1);
+    assert_eq!(logs.iter_thread(thread::current().id()).count(), 1);
+    let expected = write_value(GPIO.out().addr(), 0x1);
+    given!(thread_log(dut), require_seq!(vec![&expected]));
+}
+
+#[test]
+fn reads_of_different_threads_are_not_merged() {
+    let mock = init_mock(None);
+    let _ = unsafe { GPIO.r#in().read() };
+    let dut = thread::spawn({
+        let mock = mock.clone();
+        move || {
+            init_mock(Some(mock));
+            let _ = unsafe { GPIO.r#in().read() };
+        }
+    });
+    dut.join().unwrap();
+    assert_eq!(regmock_rs::logs().log.len(), 2);
+}
+
+#[test]
+fn logs_of_identical_runs_are_equal() {
+    let run = || {
+        let mut mock = Regmock::default();
+        mock.write_volatile(0x100, 4, 0x1);
+        for _ in 0..3 {
+            let _ = mock.read_volatile(0x104, 4);
+        }
+        mock
+    };
+    let first = run();
+    let second = thread::spawn(move || run().log.log.clone()).join().unwrap();
+    // sequence numbers, times and threads are not compared
+    assert_eq!(first.log.log, second);
+}
@@ -0,0 +1,89 @@
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::logfile::{LogFile, LogFileError, FORMAT_VERSION};
+use regmock_rs::utils::access_gen::{read_value, write_value};
+use regmock_rs::utils::RegisterAccess;
+use regmock_rs::{given, require_seq};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+fn record() -> LogFile {
+    let mock = init_mock(None);
+    unsafe {
+        GPIO.out().write(gpio::Out::new(0x1));
+        let _ = GPIO.r#in().read();
+        let _ = GPIO.r#in().read();
+        let _ = GPIO.r#in().read();
+    }
+    let log_file = mock.lock().unwrap().log_file();
+    log_file
+}
+
+#[test]
+fn json_round_trip_keeps_counts_and_names() {
+    let log_file = record();
+    assert_eq!(log_file.version, FORMAT_VERSION);
+    assert_eq!(log_file.entries.len(), 2);
+    assert_eq!(log_file.entries[0].name.as_deref(), Some("GPIO.out()"));
+    assert_eq!(log_file.entries[1].name.as_deref(), Some("GPIO.r#in()"));
+    assert_eq!(log_file.entries[1].count, 3);
+
+    let json = log_file.to_json().unwrap();
+    assert!(json.contains(r#""type": "write""#));
+    assert!(json.contains(r#""count": 3"#));
+    assert_eq!(LogFile::from_json(&json).unwrap(), log_file);
+}
+
+#[test]
+fn golden_file_can_be_matched() {
+    let path = std::env::temp_dir().join(format!("regmock-golden-{}.json", std::process::id()));
+    record().save(&path).unwrap();
+    let golden = LogFile::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let log = golden.to_log();
+    let write = write_value(GPIO.out().ptr() as usize, 0x1);
+    let read = read_value(GPIO.r#in().ptr() as usize, 0x0);
+    given!(
+        log.iter_full(),
+        require_seq!(vec![&write, &read, &read, &read])
+    );
+}
+
+#[test]
+fn csv_has_one_line_per_entry() {
+    let mut csv = Vec::new();
+    record().write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<_> = csv.lines().collect();
+    assert_eq!(
+        lines[0],
+        "seq,time_ns,type,addr,name,len,before,after,mask,count"
+    );
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1].starts_with("0,0,write,"));
+    assert!(lines[1].contains(",GPIO.out(),4,0x00000000,0x00000001,,1"));
+    assert!(lines[2].starts_with("1,0,read,"));
+    assert!(lines[2].ends_with(",3"));
+}
+
+#[test]
+fn loading_invalid_files_fails() {
+    assert!(matches!(
+        LogFile::from_json("{ \"entries\": [] }"),
+        Err(LogFileError::Json(_))
+    ));
+    assert!(matches!(
+        LogFile::from_json("{ \"version\": 99, \"entries\": [] }"),
+        Err(LogFileError::Version(99))
+    ));
+    assert!(matches!(
+        LogFile::load("does/not/exist.json"),
+        Err(LogFileError::Io(_))
+    ));
+    assert!(matches!(
+        RegisterAccess::seq_from_json("[{ \"type\": \"jump\" }]"),
+        Err(LogFileError::Json(_))
+    ));
+}
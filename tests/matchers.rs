@@ -604,3 +604,376 @@ pub fn fail_skip_log_not_skipping() {
         }
     }
 }
+
+#[cfg(test)]
+mod combinators {
+    use regmock_rs::matchers::{
+        all_of, then, AllWritesBeforeWritesTo, LogMatcher, MatchError, NotWrittenMatcher,
+        ReadLastMatcher, ValuesWrittenAre, WrittenOnceMatcher, WrittenToBeforeWriteTo,
+    };
+    use regmock_rs::{
+        require_seq, require_subseq,
+        utils::access_gen::{read_value, write_value},
+        utils::{RegisterAccess, Regmock},
+    };
+    use test_pac::{RegisterValue, SPI};
+
+    use super::*;
+
+    #[test]
+    pub fn all_of_matches() {
+        init_mock(None);
+
+        unsafe {
+            SPI.ctrl().init(|r| r);
+            SPI.tx().init(|r| r);
+
+            given!(
+                regmock_rs::logs().iter_full(),
+                all_of((
+                    require_reg!(SPI.ctrl(), written_once),
+                    require_reg!(SPI.ctrl(), all_writes_before_writes_to(SPI.tx())),
+                    require_reg!(SPI.status(), not_written),
+                ))
+            );
+        }
+    }
+
+    #[test]
+    pub fn all_of_collects_failures() {
+        init_mock(None);
+
+        unsafe {
+            SPI.tx().init(|r| r);
+            SPI.ctrl().init(|r| r);
+        }
+        let logs = regmock_rs::logs();
+        let error = all_of((
+            require_reg!(SPI.ctrl(), written_once),
+            require_reg!(SPI.ctrl(), all_writes_before_writes_to(SPI.tx())),
+            require_reg!(SPI.tx(), not_written),
+        ))
+        .r#match(logs.iter_full())
+        .unwrap_err();
+        assert_eq!(error.name, "AllOf");
+        assert_eq!(error.reason, "2 of 3 matchers failed");
+        let names: Vec<_> = error.causes.iter().map(|cause| cause.name).collect();
+        assert_eq!(names, ["AllWritesBeforeWritesTo", "NotWrittenMatcher"]);
+    }
+
+    #[test]
+    pub fn any_of_matches() {
+        init_mock(None);
+
+        unsafe {
+            SPI.ctrl().init(|r| r);
+            SPI.ctrl().init(|r| r);
+
+            given!(
+                regmock_rs::logs().iter_full(),
+                any_of((
+                    require_reg!(SPI.ctrl(), written_once),
+                    require_reg!(SPI.ctrl(), values_written_are([0u32, 0u32])),
+                ))
+            );
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "AnyOf because:\n'none of the 2 matchers matched'\n  \
+                               WrittenOnceMatcher because:\n  'Register: SPI.ctrl() was written to 0 times'\n  \
+                               ReadLastMatcher because:\n  'Register: SPI.ctrl() was not accessed.'")]
+    pub fn any_of_fails_with_tree() {
+        init_mock(None);
+
+        given!(
+            full_log,
+            any_of((
+                require_reg!(SPI.ctrl(), written_once),
+                require_reg!(SPI.ctrl(), read_last),
+            ))
+        );
+    }
+
+    #[test]
+    pub fn not_matches() {
+        init_mock(None);
+
+        unsafe {
+            SPI.ctrl().init(|r| r);
+
+            given!(full_log, not(require_reg!(SPI.ctrl(), not_written)));
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "NotWrittenMatcher matched, but was expected to fail")]
+    pub fn not_fails() {
+        init_mock(None);
+
+        given!(full_log, not(require_reg!(SPI.ctrl(), not_written)));
+    }
+
+    #[test]
+    pub fn then_matches() {
+        init_mock(None);
+
+        unsafe {
+            let _ = SPI.status().read();
+            SPI.ctrl().init(|r| r.set_raw(0x1));
+            let _ = SPI.status().read();
+            SPI.tx().init(|r| r.set_raw(0x2));
+        }
+        let r0 = read_value(SPI.status().addr(), 0);
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        given!(
+            full_log,
+            then(
+                require_seq!(vec![&r0, &w0]),
+                all_of((
+                    require_reg!(SPI.tx(), written_once),
+                    require_reg!(SPI.ctrl(), not_written),
+                ))
+            )
+        );
+    }
+
+    #[test]
+    pub fn then_splits_where_first_stops_matching() {
+        init_mock(None);
+
+        unsafe {
+            SPI.ctrl().init(|r| r.set_raw(0x1));
+            let _ = SPI.status().read();
+            SPI.ctrl().init(|r| r.set_raw(0x2));
+            SPI.tx().init(|r| r.set_raw(0x3));
+        }
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        let r0 = read_value(SPI.status().addr(), 0);
+        let w1 = write_value(SPI.ctrl().addr(), 0x2);
+        let w2 = write_value(SPI.tx().addr(), 0x3);
+        // the sequence matchers stop after the last expected access
+        given!(
+            full_log,
+            then(
+                require_subseq!(vec![&w0]),
+                require_seq!(vec![&r0, &w1, &w2])
+            )
+        );
+        // SPI.ctrl() is written once until the second write to it
+        given!(
+            full_log,
+            then(
+                require_reg!(SPI.ctrl(), written_once),
+                require_seq!(vec![&w1, &w2])
+            )
+        );
+    }
+
+    #[test]
+    pub fn then_reports_failed_part() {
+        init_mock(None);
+
+        unsafe {
+            SPI.ctrl().init(|r| r.set_raw(0x1));
+            SPI.ctrl().init(|r| r.set_raw(0x2));
+        }
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        let logs = regmock_rs::logs();
+        let error = then(
+            require_seq!(vec![&w0]),
+            require_reg!(SPI.ctrl(), not_written),
+        )
+        .r#match(logs.iter_full())
+        .unwrap_err();
+        assert_eq!(
+            error,
+            MatchError {
+                name: "Then",
+                reason: "second matcher failed on the log after the first 1 accesses".to_string(),
+                causes: vec![MatchError {
+                    name: "NotWrittenMatcher",
+                    reason: "Register: SPI.ctrl() was written to 1 times".to_string(),
+                    causes: vec![],
+                }],
+            }
+        );
+    }
+
+    /// Split of [`then`] found by matching every beginning of the log, see
+    /// [`LogMatcher::match_prefix`].
+    fn split_by_prefixes<'log, M>(matcher: &M, log: &[&'log RegisterAccess]) -> Option<usize>
+    where
+        M: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let matches: Vec<_> = (0..=log.len())
+            .map(|end| matcher.clone().r#match(log[..end].to_vec()).is_ok())
+            .collect();
+        let start = matches.iter().position(|matched| *matched)?;
+        let stop = matches[start..].iter().position(|matched| !matched);
+        Some(stop.map_or(log.len(), |stop| start + stop - 1))
+    }
+
+    fn assert_split<'log, M>(matcher: M, log: &[&'log RegisterAccess])
+    where
+        M: LogMatcher<'log, Vec<&'log RegisterAccess>> + Clone,
+    {
+        let expected = split_by_prefixes(&matcher, log);
+        assert_eq!(matcher.match_prefix(log).ok(), expected, "{log:#?}");
+    }
+
+    #[test]
+    pub fn register_matchers_split_where_they_stop_matching() {
+        init_mock(None);
+        let (a, b) = (0x100, 0x104);
+        // pseudo random logs of writes and reads of three registers
+        let mut state = 1u32;
+        for len in (0..300).map(|i| i % 9) {
+            let mut mock = Regmock::default();
+            for _ in 0..len {
+                state = state.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+                let addr = [a, b, 0x108][(state >> 16) as usize % 3];
+                match (state >> 20) % 3 {
+                    0 => {
+                        let _ = mock.read_volatile(addr, 4);
+                    }
+                    value => mock.write_volatile(addr, 4, value as u64),
+                }
+            }
+            let log: Vec<_> = mock.log.iter_full().collect();
+            assert_split(ValuesWrittenAre::new(a, [1u64, 2]), &log);
+            assert_split(ValuesWrittenAre::new(a, Vec::<u64>::new()), &log);
+            assert_split(WrittenToBeforeWriteTo::new(a, b), &log);
+            assert_split(AllWritesBeforeWritesTo::new(a, b), &log);
+            assert_split(WrittenOnceMatcher::new(a), &log);
+            assert_split(NotWrittenMatcher::new(b), &log);
+            assert_split(ReadLastMatcher::new(a), &log);
+        }
+    }
+}
+
+#[cfg(test)]
+mod sequence_variants {
+    use regmock_rs::matchers::LogMatcher;
+    use regmock_rs::{
+        require_subseq, require_unordered, require_window,
+        utils::access_gen::{read, read_value, write_value},
+    };
+    use test_pac::{RegisterValue, SPI};
+
+    use super::*;
+
+    unsafe fn init_spi() {
+        let _ = SPI.status().read();
+        SPI.ctrl().init(|r| r.set_raw(0x1));
+        let _ = SPI.status().read();
+        SPI.tx().init(|r| r.set_raw(0x2));
+        SPI.ctrl().init(|r| r.set_raw(0x3));
+    }
+
+    #[test]
+    pub fn subsequence() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        let w1 = write_value(SPI.ctrl().addr(), 0x3);
+        given!(full_log, require_subseq!(vec![&w0, &w1]));
+    }
+
+    #[test]
+    pub fn subsequence_reports_where_matching_stopped() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        let w1 = write_value(SPI.ctrl().addr(), 0x3);
+        let w2 = write_value(SPI.tx().addr(), 0x2);
+        let logs = regmock_rs::logs();
+        let error = require_subseq!(vec![&w0, &w1, &w2])
+            .r#match(logs.iter_full())
+            .unwrap_err();
+        assert_eq!(
+            error.reason,
+            "Found 2 of 3 expected accesses in order, matching stopped after log index 4: \
+             WRITE SPI.ctrl() (0x00008204) len:4 0x00000001 -> 0x00000003\n\
+             Expected accesses not found:\n\
+             WRITE SPI.tx() (0x00008208) -> 0x00000002"
+        );
+    }
+
+    #[test]
+    pub fn unordered() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let w0 = write_value(SPI.tx().addr(), 0x2);
+        let w1 = write_value(SPI.ctrl().addr(), 0x1);
+        let r0 = read(SPI.status().addr());
+        given!(full_log, require_unordered!(vec![&w0, &r0, &w1, &r0]));
+    }
+
+    #[test]
+    pub fn unordered_reassigns_ambiguous_accesses() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        // the first expected access could take the write of 0x1 that is
+        // needed by the second one
+        let any_ctrl = write_value(SPI.ctrl().addr(), 0x1);
+        let mut any_write = any_ctrl.clone();
+        any_write.after = None;
+        given!(full_log, require_unordered!(vec![&any_write, &any_ctrl]));
+    }
+
+    #[test]
+    pub fn unordered_reports_missing_accesses() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let r0 = read(SPI.status().addr());
+        let w0 = write_value(SPI.tx().addr(), 0x4);
+        let logs = regmock_rs::logs();
+        let error = require_unordered!(vec![&r0, &r0, &r0, &w0])
+            .r#match(logs.iter_full())
+            .unwrap_err();
+        assert_eq!(
+            error.reason,
+            "2 of 4 expected accesses were not found in the log of 5 accesses:\n\
+             READ SPI.status() (0x00008200)\n\
+             WRITE SPI.tx() (0x00008208) -> 0x00000004"
+        );
+    }
+
+    #[test]
+    pub fn window() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let r0 = read_value(SPI.status().addr(), 0);
+        let w0 = write_value(SPI.tx().addr(), 0x2);
+        given!(full_log, require_window!(vec![&r0, &w0]));
+    }
+
+    #[test]
+    pub fn window_reports_longest_match() {
+        init_mock(None);
+
+        unsafe { init_spi() };
+        let w0 = write_value(SPI.ctrl().addr(), 0x1);
+        let w1 = write_value(SPI.tx().addr(), 0x2);
+        let logs = regmock_rs::logs();
+        let error = require_window!(vec![&w0, &w1])
+            .r#match(logs.iter_full())
+            .unwrap_err();
+        assert_eq!(
+            error.reason,
+            "Log of 5 accesses does not contain the 2 expected accesses contiguously. \
+             The longest match of 1 accesses starts at log index 1, matching stopped at log index 2 with\n\
+             expected: WRITE SPI.tx() (0x00008208) -> 0x00000002\n\
+             actual:   READ SPI.status() (0x00008200) len:4 0x00000000 -> 0x00000000\n\
+             Expected accesses not found:\n\
+             WRITE SPI.tx() (0x00008208) -> 0x00000002"
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, spi, RegisterValue, GPIO, SPI};
+use regmock_rs::database::{MemoryMap, RegisterDatabase};
+use regmock_rs::utils::{AccessPolicy, RegisterAccessType, Regmock, ViolationKind};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+fn mapped_mock(policy: AccessPolicy) -> Regmock {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    let mut mock = Regmock::strict(database.clone(), policy);
+    mock.memory_map = Some(MemoryMap::from_database(&database));
+    mock
+}
+
+#[test]
+fn record_unmapped_accesses() {
+    let mut mock = mapped_mock(AccessPolicy::Record);
+    mock.write_volatile(0x8018, 4, 0x1);
+    assert_eq!(mock.read_volatile(0x8018, 4), 0x1);
+    // mapped, but the access is wider than the register
+    let _ = mock.read_volatile(0x8204, 8);
+    let _ = mock.read_volatile(SPI.ctrl().addr(), 4);
+
+    let violations: Vec<_> = mock
+        .log
+        .violations
+        .iter()
+        .map(|v| {
+            (
+                v.kind.clone(),
+                v.access.ty.clone().unwrap(),
+                v.access.addr.unwrap(),
+                v.access.len.unwrap(),
+            )
+        })
+        .collect();
+    let near = |name: &str| ViolationKind::Unmapped {
+        nearest: Some(name.to_owned()),
+    };
+    assert_eq!(
+        violations,
+        vec![
+            (
+                near("TIMER.timercluster()[1].max()"),
+                RegisterAccessType::WRITE,
+                0x8018,
+                4
+            ),
+            (
+                near("TIMER.timercluster()[1].max()"),
+                RegisterAccessType::READ,
+                0x8018,
+                4
+            ),
+            (near("SPI.ctrl()"), RegisterAccessType::READ, 0x8204, 8),
+        ]
+    );
+}
+
+#[test]
+fn drop_unmapped_accesses() {
+    let mut mock = mapped_mock(AccessPolicy::Drop);
+    mock.write_volatile(0x8100, 4, 0x1);
+    assert_eq!(mock.read_volatile(0x8100, 4), 0x0);
+    assert!(!mock.register_mocks.contains_key(&0x8100));
+    assert_eq!(mock.log.violations.len(), 2);
+}
+
+#[test]
+fn memory_map_without_database() {
+    let mut mock = Regmock::default();
+    mock.access_policy = AccessPolicy::Record;
+    let mut map = MemoryMap::new();
+    map.add("SPI", 0x8200..0x8210);
+    mock.memory_map = Some(map);
+
+    mock.write_volatile(0x8204, 4, 0x1);
+    mock.write_volatile(0x8210, 4, 0x1);
+    assert_eq!(mock.log.violations.len(), 1);
+    assert_eq!(
+        mock.log.violations[0].kind,
+        ViolationKind::Unmapped {
+            nearest: Some("SPI".to_owned())
+        }
+    );
+}
+
+#[test]
+fn access_beyond_end_of_address_space_is_unmapped() {
+    let mut mock = Regmock::default();
+    mock.access_policy = AccessPolicy::Record;
+    let mut map = MemoryMap::new();
+    map.add("TOP", usize::MAX - 0x10..usize::MAX);
+    mock.memory_map = Some(map);
+
+    mock.write_volatile(usize::MAX - 1, 4, 0x1);
+    mock.write_volatile(usize::MAX - 1, 4, 0x2);
+    assert_eq!(mock.log.violations.len(), 2);
+    assert_eq!(
+        mock.log.violations[0].kind,
+        ViolationKind::Unmapped {
+            nearest: Some("TOP".to_owned())
+        }
+    );
+}
+
+#[test]
+#[should_panic(expected = "nearest: Some(\"SPI.ctrl()\")")]
+fn panic_on_unmapped_access() {
+    let mut mock = Regmock::default();
+    mock.access_policy = AccessPolicy::Panic;
+    let mut map = MemoryMap::new();
+    map.add("SPI.ctrl()", SPI.ctrl().addr()..SPI.ctrl().addr() + 4);
+    mock.memory_map = Some(map);
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        SPI.ctrl().write(spi::Ctrl::new(0x1));
+        GPIO.we().write(gpio::We::new(0x1));
+    }
+}
+
+#[test]
+fn silent_accesses_are_not_checked() {
+    init_mock(Some(Arc::new(Mutex::new(mapped_mock(AccessPolicy::Panic)))));
+
+    regmock_rs::silent(|| regmock_rs::write_fn(0x9000, 4, 0x1));
+    assert_eq!(unsafe { GPIO.we().read().get_raw() }, 0x0);
+    assert!(regmock_rs::logs().violations.is_empty());
+}
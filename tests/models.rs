@@ -0,0 +1,125 @@
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pac::{timer, RegisterValue, TIMER};
+use regmock_rs::model::PeripheralModel;
+use regmock_rs::utils::{AccessContext, RegisterMap, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+/// Counter that counts up by the elapsed milliseconds while enabled.
+#[derive(Debug, Default)]
+struct TimerModel {
+    enabled: bool,
+    writes: usize,
+    resets: usize,
+}
+
+impl TimerModel {
+    fn ctrlstat() -> usize {
+        TIMER.timercluster()[0].ctrlstat().addr()
+    }
+
+    fn count() -> usize {
+        TIMER.timercluster()[0].count().addr()
+    }
+}
+
+impl PeripheralModel for TimerModel {
+    fn range(&self) -> Range<usize> {
+        Self::ctrlstat()..Self::ctrlstat() + 0xc
+    }
+
+    fn on_write(&mut self, ctx: &mut AccessContext) -> u64 {
+        self.writes += 1;
+        if ctx.register == Self::ctrlstat() {
+            self.enabled = ctx.value & 0x1 != 0;
+        }
+        ctx.value
+    }
+
+    fn on_reset(&mut self, registers: &mut RegisterMap) {
+        self.enabled = false;
+        self.resets += 1;
+        registers.insert(Self::count(), 0x10);
+    }
+
+    fn on_tick(&mut self, mock: &mut Regmock, elapsed: Duration) {
+        if self.enabled {
+            *mock.register_mocks.entry(Self::count()).or_default() += elapsed.as_millis() as u64;
+        }
+    }
+}
+
+#[test]
+fn accesses_are_routed_to_model() {
+    let mock = init_mock(None);
+    let handle = mock.lock().unwrap().attach_model(TimerModel::default());
+
+    unsafe {
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x1));
+        // outside of the model's range
+        TIMER.timercluster()[1]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x1));
+    }
+    mock.lock().unwrap().advance_time(Duration::from_millis(5));
+    assert_eq!(
+        unsafe { TIMER.timercluster()[0].count().read().get_raw() },
+        0x5
+    );
+
+    let mock = mock.lock().unwrap();
+    let model = mock.model(&handle).unwrap();
+    assert!(model.enabled);
+    assert_eq!(model.writes, 1);
+}
+
+#[test]
+fn reset_notifies_model() {
+    let mut mock = Regmock::default();
+    let handle = mock.attach_model(TimerModel::default());
+    mock.model_mut(&handle).unwrap().enabled = true;
+
+    mock.reset();
+    mock.advance_time(Duration::from_millis(5));
+    assert_eq!(mock.read_volatile(TimerModel::count(), 4), 0x10);
+    assert_eq!(mock.model(&handle).unwrap().resets, 1);
+}
+
+#[test]
+fn exact_callbacks_take_precedence_over_models() {
+    let mut mock = Regmock::default();
+    let handle = mock.attach_model(TimerModel::default());
+    mock.write_fn
+        .insert(TimerModel::ctrlstat(), Box::new(|_, _, val| val));
+
+    mock.write_volatile(TimerModel::ctrlstat(), 4, 0x1);
+    mock.write_volatile(TimerModel::count(), 4, 0x1);
+    assert_eq!(mock.model(&handle).unwrap().writes, 1);
+}
+
+#[test]
+fn detach_model() {
+    let mock = Arc::new(Mutex::new(Regmock::default()));
+    let handle = mock.lock().unwrap().attach_model(TimerModel::default());
+    let other = mock.lock().unwrap().attach_model(TimerModel::default());
+
+    mock.lock()
+        .unwrap()
+        .write_volatile(TimerModel::ctrlstat(), 4, 0x1);
+    let model = mock.lock().unwrap().detach_model(handle).unwrap();
+    assert_eq!(model.writes, 1);
+    assert!(mock.lock().unwrap().model(&handle).is_none());
+
+    // accesses are routed to the remaining model
+    mock.lock()
+        .unwrap()
+        .write_volatile(TimerModel::ctrlstat(), 4, 0x1);
+    assert_eq!(mock.lock().unwrap().model(&other).unwrap().writes, 1);
+}
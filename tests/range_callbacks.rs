@@ -0,0 +1,80 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{spi, timer, RegisterValue, SPI, TIMER};
+use regmock_rs::database::RegisterDatabase;
+use regmock_rs::utils::{CallbackRange, RangeAccess, RegisterMap, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+#[test]
+fn peripheral_wide_callback() {
+    let database = RegisterDatabase::from_svd(SVD_PATH).unwrap();
+    let spi_range = database.peripheral_range("SPI").unwrap();
+    assert_eq!(spi_range, SPI.status().addr()..SPI.rx().addr() + 4);
+
+    let accesses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = accesses.clone();
+    let mut mock = Regmock::default();
+    mock.write_range_fn.push((
+        spi_range.into(),
+        Box::new(move |_: &mut RegisterMap, access: RangeAccess, _, val| {
+            recorded.lock().unwrap().push((access.addr, access.offset));
+            val
+        }),
+    ));
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        SPI.ctrl().write(spi::Ctrl::new(0x1));
+        SPI.tx().write(spi::Tx::new(0x2));
+        TIMER.timercluster()[0]
+            .max()
+            .write(timer::timercluster::Max::new(0x3));
+    }
+    assert_eq!(
+        *accesses.lock().unwrap(),
+        vec![(SPI.ctrl().addr(), 0x4), (SPI.tx().addr(), 0x8)]
+    );
+}
+
+#[test]
+fn strided_cluster_callback() {
+    let first = TIMER.timercluster()[0].ctrlstat().addr();
+    let stride = TIMER.timercluster()[1].ctrlstat().addr() - first;
+
+    let mut mock = Regmock::default();
+    mock.read_range_fn.push((
+        CallbackRange::array(first, 0x8, stride, 2),
+        Box::new(|_: &mut RegisterMap, access: RangeAccess, _| {
+            (access.index << 8 | access.offset) as u64
+        }),
+    ));
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        assert_eq!(TIMER.timercluster()[0].ctrlstat().read().get_raw(), 0x000);
+        assert_eq!(TIMER.timercluster()[1].ctrlstat().read().get_raw(), 0x100);
+        assert_eq!(TIMER.timercluster()[1].count().read().get_raw(), 0x104);
+        // `max` is outside of the 8 byte blocks
+        assert_eq!(TIMER.timercluster()[1].max().read().get_raw(), 0x0);
+    }
+}
+
+#[test]
+fn exact_callbacks_take_precedence() {
+    let mut mock = Regmock::default();
+    mock.read_range_fn
+        .push((CallbackRange::block(0x100, 0x10), Box::new(|_, _, _| 0xAA)));
+    mock.read_range_fn
+        .push((CallbackRange::block(0x100, 0x20), Box::new(|_, _, _| 0xBB)));
+    mock.read_fn.insert(0x104, Box::new(|_, _| 0xCC));
+
+    assert_eq!(mock.read_volatile(0x100, 4), 0xAA);
+    assert_eq!(mock.read_volatile(0x104, 4), 0xCC);
+    assert_eq!(mock.read_volatile(0x110, 4), 0xBB);
+    assert_eq!(mock.read_volatile(0x120, 4), 0x0);
+}
@@ -0,0 +1,182 @@
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::install;
+use regmock_rs::replay::Replay;
+use regmock_rs::utils::access_gen::{read_value, write_value};
+use regmock_rs::utils::{RegisterAccess, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+/// Driver code under test: set an output and wait for the input to follow.
+fn toggle(value: u32) {
+    unsafe {
+        GPIO.out().write(gpio::Out::new(value));
+        while GPIO.r#in().read().get_raw() != value {}
+    }
+}
+
+fn out() -> usize {
+    GPIO.out().ptr() as usize
+}
+
+fn r#in() -> usize {
+    GPIO.r#in().ptr() as usize
+}
+
+fn replaying(trace: Vec<RegisterAccess>) -> Arc<Mutex<Regmock>> {
+    let mock = init_mock(None);
+    mock.lock().unwrap().replay = Some(Replay::new(trace));
+    mock
+}
+
+#[test]
+fn reads_are_served_from_the_trace() {
+    let mock = replaying(vec![
+        write_value(out(), 0x1),
+        read_value(r#in(), 0x0),
+        read_value(r#in(), 0x0),
+        read_value(r#in(), 0x1),
+    ]);
+    toggle(0x1);
+
+    let mut mock = mock.lock().unwrap();
+    assert!(mock.replay.as_ref().unwrap().is_finished());
+    assert_eq!(mock.log.len_full(), 4);
+    assert_eq!(mock.verify(), Ok(()));
+}
+
+#[test]
+fn recorded_golden_file_replays() {
+    init_mock(None);
+    let recorded = Arc::new(Mutex::new(Regmock::default()));
+    recorded.lock().unwrap().write_fn.insert(
+        out(),
+        Box::new(|regs, _, value| {
+            regs.insert(r#in(), value);
+            value
+        }),
+    );
+    {
+        let _guard = install(recorded.clone());
+        toggle(0x3);
+    }
+    let path = std::env::temp_dir().join(format!("regmock-replay-{}.json", std::process::id()));
+    recorded.lock().unwrap().log_file().save(&path).unwrap();
+
+    let replay = Replay::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(replay.remaining(), 2);
+    let mock = Arc::new(Mutex::new(Regmock::default()));
+    mock.lock().unwrap().replay = Some(replay);
+    let _guard = install(mock.clone());
+    toggle(0x3);
+    assert_eq!(mock.lock().unwrap().verify(), Ok(()));
+}
+
+#[test]
+fn divergent_write_is_reported_with_context() {
+    let mock = replaying(vec![
+        write_value(out(), 0x1),
+        read_value(r#in(), 0x1),
+        write_value(out(), 0x2),
+        read_value(r#in(), 0x2),
+    ]);
+    toggle(0x1);
+
+    let panic = catch_unwind(AssertUnwindSafe(|| toggle(0x3))).unwrap_err();
+    let message = panic.downcast_ref::<String>().unwrap();
+    assert_eq!(
+        message.as_str(),
+        "Replay diverged from the trace at access 2 of 4:\n\
+         after\n  \
+         WRITE GPIO.out() (0x0000842C) -> 0x00000001\n  \
+         READ GPIO.r#in() (0x00008420) -> 0x00000001\n\
+         expected\n  \
+         WRITE GPIO.out() (0x0000842C) -> 0x00000002\n\
+         but got\n  \
+         WRITE GPIO.out() (0x0000842C) len:4 0x00000001 -> 0x00000003"
+    );
+    // the divergence is not reported again when the mock is dropped
+    assert!(mock
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .replay
+        .is_none());
+}
+
+#[test]
+#[should_panic(expected = "expected the end of the trace")]
+fn accesses_after_the_end_of_the_trace_diverge() {
+    replaying(vec![write_value(out(), 0x1)]);
+    toggle(0x1);
+}
+
+#[test]
+fn unfinished_trace_fails_verification() {
+    let mock = replaying(vec![
+        write_value(out(), 0x1),
+        read_value(r#in(), 0x1),
+        write_value(out(), 0x0),
+    ]);
+    toggle(0x1);
+
+    let mut mock = mock.lock().unwrap();
+    assert_eq!(
+        mock.verify(),
+        Err(vec![
+            "replay ended after 2 of 3 accesses of the trace, next expected \
+             WRITE GPIO.out() (0x0000842C) -> 0x00000000"
+                .to_string()
+        ])
+    );
+}
+
+#[test]
+fn trace_is_not_replayed_without_callbacks() {
+    let mock = replaying(vec![read_value(r#in(), 0x1)]);
+    mock.lock().unwrap().callback_enabled = false;
+    assert_eq!(unsafe { GPIO.r#in().read().get_raw() }, 0x0);
+
+    let mut mock = mock.lock().unwrap();
+    mock.callback_enabled = true;
+    assert_eq!(mock.replay.as_ref().unwrap().position(), 0);
+    mock.replay = None;
+}
+
+#[test]
+fn writes_are_compared_with_the_written_value() {
+    let svd = r#"
+<device>
+    <peripherals>
+        <peripheral>
+            <name>p</name>
+            <baseAddress>0x100</baseAddress>
+            <registers>
+                <register>
+                    <name>status</name>
+                    <addressOffset>0x0</addressOffset>
+                    <fields>
+                        <field><name>flags</name><bitRange>[3:0]</bitRange><modifiedWriteValues>oneToClear</modifiedWriteValues></field>
+                    </fields>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+    let mut mock = Regmock::from_svd_str(svd).unwrap();
+    // a debugger records the value on the bus, not the cleared flags
+    mock.replay = Some(Replay::new(vec![
+        read_value(0x100, 0x5),
+        write_value(0x100, 0x5),
+    ]));
+
+    let flags = mock.read_volatile(0x100, 4);
+    mock.write_volatile(0x100, 4, flags);
+    assert_eq!(mock.register_mocks[&0x100], 0x0);
+    assert_eq!(mock.verify(), Ok(()));
+}
@@ -0,0 +1,99 @@
+use std::path::PathBuf;
+
+use pac::{gpio, RegisterValue, GPIO};
+use regmock_rs::assert_log_snapshot;
+use regmock_rs::snapshot::check_snapshot;
+use regmock_rs::utils::Regmock;
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("regmock-{name}-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    dir
+}
+
+#[test]
+fn log_matches_stored_snapshot() {
+    init_mock(None);
+    unsafe {
+        GPIO.out().write(gpio::Out::new(0x1));
+        let _ = GPIO.r#in().read();
+        let _ = GPIO.r#in().read();
+        GPIO.out().modify(|r| r.set_raw(r.get_raw() | 0x2));
+    }
+    assert_log_snapshot!("gpio_toggle");
+}
+
+#[test]
+fn snapshot_of_explicit_mock() {
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x100, 4, 0x1);
+    assert_eq!(
+        mock.format_snapshot(),
+        "WRITE 0x00000100 len:4 0x00000000 -> 0x00000001\n"
+    );
+    assert_log_snapshot!("explicit_mock", mock);
+}
+
+#[test]
+fn missing_snapshot_is_written_for_review() {
+    let dir = temp_dir("missing-snapshot");
+    let message = check_snapshot(&dir, "new", "WRITE 0x00000100\n", false).unwrap_err();
+    assert!(message.starts_with("Snapshot 'new' does not exist yet."));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("new.snap.new")).unwrap(),
+        "WRITE 0x00000100\n"
+    );
+    assert!(!dir.join("new.snap").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn changed_snapshot_fails_with_diff() {
+    let dir = temp_dir("changed-snapshot");
+    std::fs::create_dir_all(&dir).unwrap();
+    let old: String = (0..8).map(|i| format!("WRITE 0x{i:08X}\n")).collect();
+    std::fs::write(dir.join("changed.snap"), &old).unwrap();
+    let new = old.replace("WRITE 0x00000006", "READ 0x00000006");
+
+    let message = check_snapshot(&dir, "changed", &new, false).unwrap_err();
+    assert!(message.contains(
+        "  ... 4 unchanged\n  \
+         WRITE 0x00000004\n  \
+         WRITE 0x00000005\n\
+         - WRITE 0x00000006\n\
+         + READ 0x00000006\n  \
+         WRITE 0x00000007\n"
+    ));
+    assert_eq!(
+        std::fs::read_to_string(dir.join("changed.snap.new")).unwrap(),
+        new
+    );
+
+    // the pending snapshot is removed once the log matches again
+    assert_eq!(check_snapshot(&dir, "changed", &old, false), Ok(()));
+    assert!(!dir.join("changed.snap.new").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn update_accepts_new_and_changed_snapshots() {
+    let dir = temp_dir("update-snapshot");
+    assert_eq!(
+        check_snapshot(&dir, "log", "WRITE 0x00000100\n", true),
+        Ok(())
+    );
+    assert_eq!(
+        check_snapshot(&dir, "log", "READ 0x00000100\n", true),
+        Ok(())
+    );
+    assert_eq!(
+        std::fs::read_to_string(dir.join("log.snap")).unwrap(),
+        "READ 0x00000100\n"
+    );
+    assert!(!dir.join("log.snap.new").exists());
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,159 @@
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, RegisterValue, GPIO, SPI, TIMER};
+use regmock_rs::svd::{self, SvdError};
+use regmock_rs::utils::Regmock;
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+static RESET_SVD: &str = r#"
+<device>
+    <name>reset</name>
+    <size>32</size>
+    <resetValue>0x0</resetValue>
+    <resetMask>0xFFFFFFFF</resetMask>
+    <peripherals>
+        <peripheral>
+            <name>gpio</name>
+            <baseAddress>0x8400</baseAddress>
+            <resetValue>0x11</resetValue>
+            <registers>
+                <register>
+                    <name>in</name>
+                    <addressOffset>0x20</addressOffset>
+                </register>
+                <register>
+                    <name>we</name>
+                    <addressOffset>0x24</addressOffset>
+                    <resetValue>0xABCD</resetValue>
+                    <resetMask>0x0FFF</resetMask>
+                </register>
+            </registers>
+        </peripheral>
+        <peripheral derivedFrom="gpio">
+            <name>gpio1</name>
+            <baseAddress>0x9400</baseAddress>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+
+#[test]
+fn load_example_svd() {
+    let registers = svd::load(SVD_PATH).unwrap();
+    assert_eq!(registers.len(), 13);
+
+    let names = [
+        (
+            TIMER.timercluster()[1].ctrlstat().addr(),
+            "TIMER.timercluster()[1].ctrlstat()",
+        ),
+        (SPI.rx().addr(), "SPI.rx()"),
+        (GPIO.r#in().addr(), "GPIO.r#in()"),
+    ];
+    for (addr, name) in names {
+        let register = registers.iter().find(|r| r.address == addr).unwrap();
+        assert_eq!(register.name, name);
+        assert_eq!(register.size, 32);
+    }
+}
+
+#[test]
+fn reset_values_and_masks() {
+    let registers = svd::parse(RESET_SVD).unwrap();
+    let reset_of = |addr: usize| {
+        registers
+            .iter()
+            .find(|r| r.address == addr)
+            .unwrap()
+            .reset_value
+    };
+    assert_eq!(reset_of(0x8420), 0x11);
+    assert_eq!(reset_of(0x8424), 0xBCD);
+    assert_eq!(reset_of(0x9420), 0x11);
+    assert_eq!(reset_of(0x9424), 0xBCD);
+}
+
+#[test]
+fn invalid_svd() {
+    assert!(matches!(svd::parse("<device>"), Err(SvdError::Xml(_))));
+    assert!(matches!(
+        svd::parse("<device><name>x</name></device>"),
+        Err(SvdError::Invalid(_))
+    ));
+    assert!(matches!(
+        svd::load("/does/not/exist.svd"),
+        Err(SvdError::Io(_))
+    ));
+}
+
+#[test]
+fn inverted_field_position() {
+    let svd = r#"
+<device>
+    <name>inverted</name>
+    <peripherals>
+        <peripheral>
+            <name>gpio</name>
+            <baseAddress>0x8400</baseAddress>
+            <registers>
+                <register>
+                    <name>out</name>
+                    <addressOffset>0x0</addressOffset>
+                    <fields>
+                        <field>
+                            <name>pin</name>
+                            <lsb>4</lsb>
+                            <msb>1</msb>
+                        </field>
+                    </fields>
+                </register>
+            </registers>
+        </peripheral>
+    </peripherals>
+</device>
+"#;
+    assert!(matches!(svd::parse(svd), Err(SvdError::Invalid(_))));
+}
+
+#[test]
+fn registers_start_at_reset_value() {
+    let mock = Regmock::from_svd_str(RESET_SVD).unwrap();
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        assert_eq!(GPIO.r#in().read().get_raw(), 0x11);
+        assert_eq!(GPIO.we().read().get_raw(), 0xBCD);
+        // registers unknown to the SVD still start at 0x0
+        assert_eq!(GPIO.out().read().get_raw(), 0x0);
+    }
+}
+
+#[test]
+fn reset_restores_reset_values() {
+    let mock = Regmock::from_svd_str(RESET_SVD).unwrap();
+    init_mock(Some(Arc::new(Mutex::new(mock))));
+
+    unsafe {
+        GPIO.we().write(gpio::We::new(0x1));
+        GPIO.out().write(gpio::Out::new(0x2));
+        assert_eq!(GPIO.we().read().get_raw(), 0x1);
+
+        regmock_rs::reset();
+
+        assert_eq!(GPIO.we().read().get_raw(), 0xBCD);
+        assert_eq!(GPIO.out().read().get_raw(), 0x0);
+    }
+    // the reset is not recorded in the log
+    assert_eq!(regmock_rs::logs().len_full(), 5);
+}
+
+#[test]
+fn from_svd_file() {
+    let mock = Regmock::from_svd(SVD_PATH).unwrap();
+    assert_eq!(mock.reset_values.len(), 13);
+}
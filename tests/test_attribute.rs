@@ -0,0 +1,55 @@
+#![cfg(feature = "macros")]
+
+use std::sync::{Arc, Mutex};
+
+use pac::{gpio, spi, RegisterValue, GPIO, SPI};
+use regmock_rs::utils::Regmock;
+use test_pac as pac;
+
+#[regmock_rs::test(pac = pac, resolver = pac::reg_name::reg_name_from_addr)]
+fn mock_is_injected(mock: Arc<Mutex<Regmock>>) {
+    unsafe { GPIO.out().write(gpio::Out::new(0x1)) };
+    let mock = mock.lock().unwrap();
+    assert_eq!(mock.log.len_full(), 1);
+    assert!(mock.format_log().contains("GPIO"));
+}
+
+#[regmock_rs::test(pac = pac)]
+fn every_test_gets_a_fresh_mock() {
+    // no state is shared with other tests
+    assert_eq!(unsafe { GPIO.out().read().get_raw() }, 0x0);
+    assert_eq!(regmock_rs::logs().len_full(), 1);
+}
+
+#[regmock_rs::test(pac = pac, svd = "test-pac/example.svd")]
+fn mock_is_loaded_from_svd(mock: Arc<Mutex<Regmock>>) {
+    assert!(mock.lock().unwrap().database.is_some());
+}
+
+#[regmock_rs::test(pac = pac)]
+fn end_of_test_checks_pass(mock: Arc<Mutex<Regmock>>) {
+    mock.lock()
+        .unwrap()
+        .at_end_of_test(|mock| match mock.register_mocks.get(&SPI.ctrl().addr()) {
+            Some(0x1) => Ok(()),
+            _ => Err("SPI was not enabled".to_string()),
+        });
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+}
+
+#[regmock_rs::test(pac = pac)]
+#[should_panic(expected = "SPI was not enabled")]
+fn end_of_test_checks_fail(mock: Arc<Mutex<Regmock>>) {
+    mock.lock()
+        .unwrap()
+        .at_end_of_test(|_| Err("SPI was not enabled".to_string()));
+}
+
+#[regmock_rs::test(pac = pac)]
+fn returns_result() -> Result<(), String> {
+    let value = unsafe { SPI.ctrl().read().get_raw() };
+    if value != 0x0 {
+        return Err(format!("unexpected value 0x{value:X}"));
+    }
+    Ok(())
+}
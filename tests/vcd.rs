@@ -0,0 +1,132 @@
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pac::{gpio, timer, RegisterValue, GPIO, TIMER};
+use regmock_rs::utils::Regmock;
+use regmock_rs::vcd::{Timebase, VcdOptions};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const SVD_PATH: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/test-pac/example.svd");
+
+fn vcd(mock: &Regmock, options: &VcdOptions) -> String {
+    let mut out = Vec::new();
+    mock.write_vcd(&mut out, options).unwrap();
+    String::from_utf8(out).unwrap()
+}
+
+#[test]
+fn signals_are_named_after_registers() {
+    let mock = init_mock(None);
+    mock.lock().unwrap().write_cost = Duration::from_micros(1);
+    unsafe {
+        GPIO.out().write(gpio::Out::new(0x1));
+        GPIO.out().write(gpio::Out::new(0x3));
+        let _ = GPIO.r#in().read();
+    }
+
+    let vcd = vcd(&mock.lock().unwrap(), &VcdOptions::default());
+    let lines: Vec<_> = vcd.lines().collect();
+    assert!(lines.contains(&"$timescale 1ns $end"));
+    assert!(lines.contains(&"$var wire 32 ! GPIO_in $end"));
+    assert!(lines.contains(&"$var wire 32 \" GPIO_out $end"));
+    let changes = lines
+        .iter()
+        .skip_while(|l| **l != "$enddefinitions $end")
+        .skip(1)
+        .copied()
+        .collect::<Vec<_>>();
+    assert_eq!(
+        changes,
+        vec![
+            "#0",
+            "$dumpvars",
+            "b0 !",
+            "b0 \"",
+            "$end",
+            "b1 \"",
+            "#1000",
+            "b11 \"",
+            "#2000",
+            "b0 !"
+        ]
+    );
+}
+
+#[test]
+fn sequence_numbers_are_used_without_virtual_time() {
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x100, 4, 0x1);
+    mock.write_volatile(0x100, 4, 0x2);
+    mock.write_volatile(0x100, 4, 0x3);
+
+    let sequence = vcd(&mock, &VcdOptions::default());
+    assert!(
+        sequence.ends_with("$end\nb1 !\n#1\nb10 !\n#2\nb11 !\n"),
+        "{sequence}"
+    );
+
+    let options = VcdOptions {
+        timebase: Timebase::Virtual,
+        ..Default::default()
+    };
+    // all changes happen at time 0
+    let virtual_time = vcd(&mock, &options);
+    assert!(
+        virtual_time.ends_with("$end\nb1 !\nb10 !\nb11 !\n"),
+        "{virtual_time}"
+    );
+}
+
+#[test]
+fn byte_accesses_change_the_register_signal() {
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x100, 4, 0x1122_3344);
+    mock.write_volatile(0x102, 1, 0xAA);
+
+    let vcd = vcd(&mock, &VcdOptions::default());
+    assert!(!vcd.contains("reg_0x00000102"), "{vcd}");
+    assert!(
+        vcd.ends_with(&format!(
+            "$end\nb{:b} !\n#1\nb{:b} !\n",
+            0x1122_3344, 0x11AA_3344
+        )),
+        "{vcd}"
+    );
+}
+
+#[test]
+fn bitfields_get_their_own_signals() {
+    let mock = Regmock::from_svd(SVD_PATH).unwrap();
+    let mock = init_mock(Some(Arc::new(Mutex::new(mock))));
+    unsafe {
+        TIMER.timercluster()[0]
+            .ctrlstat()
+            .write(timer::timercluster::Ctrlstat::new(0x21))
+    };
+
+    let options = VcdOptions {
+        timebase: Timebase::Sequence,
+        bitfields: true,
+    };
+    let vcd = vcd(&mock.lock().unwrap(), &options);
+    assert!(vcd.contains("$scope module TIMER_timercluster_0_ctrlstat $end"));
+    assert!(vcd.contains("$var wire 1 \" enable $end\n$var wire 3 # reset_in $end"));
+    // enable is a scalar, clock a vector
+    assert!(vcd.ends_with("b100001 !\n1\"\nb0 #\nb10 $\n0%\n"), "{vcd}");
+}
+
+#[test]
+fn log_can_be_exported_without_names() {
+    let mut mock = Regmock::default();
+    mock.write_volatile(0x100, 2, 0x1);
+    let mut out = Vec::new();
+    mock.log
+        .write_vcd(&mut out, &VcdOptions::default())
+        .unwrap();
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("$var wire 16 ! reg_0x00000100 $end"));
+}
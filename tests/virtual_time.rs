@@ -0,0 +1,129 @@
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use pac::{spi, RegisterValue, SPI};
+use regmock_rs::model::PeripheralModel;
+use regmock_rs::utils::{AccessContext, Callback, RegisterAccess, RegisterAccessType, Regmock};
+use test_pac as pac;
+
+mod common;
+use common::init_mock;
+
+const US: Duration = Duration::from_micros(1);
+
+#[test]
+fn accesses_cost_time() {
+    let mut mock = Regmock::default();
+    mock.read_cost = US;
+    mock.write_cost = 2 * US;
+    mock.write_volatile(0x100, 4, 0x1);
+    let _ = mock.read_volatile(0x100, 4);
+    let _ = mock.read_volatile(0x100, 4);
+    mock.advance_time(10 * US);
+    mock.write_volatile(0x100, 4, 0x2);
+    assert_eq!(mock.now(), 16 * US);
+
+    let times: Vec<_> = mock.log.iter().map(|a| a.time.unwrap()).collect();
+    // the polling reads are run-length-encoded with the time of the first read
+    assert_eq!(times, vec![Duration::ZERO, 2 * US, 14 * US]);
+
+    // timestamps are only compared if the expected access has one
+    assert_eq!(
+        RegisterAccess::new(RegisterAccessType::WRITE, 0x100, 4, 0x1, 0x2),
+        mock.log.log[2].0
+    );
+}
+
+#[test]
+fn silent_accesses_cost_no_time() {
+    let mut mock = Regmock::default();
+    mock.read_cost = US;
+    mock.callback_enabled = false;
+    let _ = mock.read_volatile(0x100, 4);
+    assert_eq!(mock.now(), Duration::ZERO);
+}
+
+#[test]
+fn events_run_in_order() {
+    let order = Arc::new(Mutex::new(Vec::new()));
+    let mut mock = Regmock::default();
+    for (at, id) in [(3, 'c'), (1, 'a'), (3, 'd'), (2, 'b'), (10, 'e')] {
+        let order = order.clone();
+        mock.schedule_at(at * US, move |mock| {
+            order.lock().unwrap().push((id, mock.now()));
+        });
+    }
+    mock.advance_time(5 * US);
+    assert_eq!(
+        *order.lock().unwrap(),
+        vec![('a', US), ('b', 2 * US), ('c', 3 * US), ('d', 3 * US)]
+    );
+    assert_eq!(mock.now(), 5 * US);
+}
+
+#[test]
+fn callback_schedules_completion() {
+    let mut mock = Regmock::default();
+    // a transfer takes 8us after writing the start bit
+    mock.write_callbacks.insert(
+        0x100,
+        Callback::new(|ctx| {
+            ctx.schedule_in(8 * US, |mock| {
+                let status = mock.register_mocks.entry(0x104).or_default();
+                *status |= 0x1;
+            });
+            ctx.value
+        }),
+    );
+    mock.write_volatile(0x100, 4, 0x1);
+    mock.advance_time(7 * US);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x0);
+    mock.advance_time(US);
+    assert_eq!(mock.read_volatile(0x104, 4), 0x1);
+}
+
+/// Sets `rxe` in the SPI status register until the baudrate delay passed.
+struct SlowSpi {
+    received_at: Option<Duration>,
+}
+
+impl PeripheralModel for SlowSpi {
+    fn range(&self) -> Range<usize> {
+        SPI.status().addr()..SPI.status().addr() + 4
+    }
+
+    fn on_read(&mut self, ctx: &mut AccessContext) -> u64 {
+        match self.received_at {
+            Some(at) if ctx.now() >= at => 0x0,
+            _ => 0x4,
+        }
+    }
+}
+
+#[test]
+fn polling_with_timeout() {
+    let mock = init_mock(None);
+    {
+        let mut mock = mock.lock().unwrap();
+        mock.read_cost = US;
+        mock.attach_model(SlowSpi {
+            received_at: Some(100 * US),
+        });
+    }
+
+    // the DUT polls the status register with a timeout of 1ms
+    let mut polls = 0;
+    while unsafe { SPI.status().read().rxe().get() } {
+        polls += 1;
+        assert!(polls < 1000, "timed out");
+    }
+    assert_eq!(polls, 100);
+
+    regmock_rs::advance_time(Duration::from_millis(1));
+    unsafe { SPI.ctrl().write(spi::Ctrl::new(0x1)) };
+    assert_eq!(
+        regmock_rs::logs().log.last().unwrap().0.time,
+        Some(Duration::from_micros(1101))
+    );
+}